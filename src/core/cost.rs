@@ -7,6 +7,13 @@ pub struct Cost {
     pub leftover_value: f32,
     pub part_area_excluded: u64,
     pub part_area_included: u64,
+    pub part_value_excluded: u64,
+    pub n_cuts: u64,
+    pub cut_length: u64,
+    /// The number of distinct cutting patterns, only set for the solution of an entire Problem
+    pub n_patterns: u64,
+    /// The number of (order, layout) pairs: every order counts once for each layout containing its parts
+    pub order_spread: u64,
 }
 
 
@@ -17,11 +24,16 @@ impl Cost {
             leftover_value: 0.0,
             part_area_excluded: 0,
             part_area_included: 0,
+            part_value_excluded: 0,
+            n_cuts: 0,
+            cut_length: 0,
+            n_patterns: 0,
+            order_spread: 0,
         }
     }
 
-    pub fn new(material_cost: u64, leftover_value: f32, part_area_excluded: u64, part_area_included: u64) -> Self {
-        Self { material_cost, leftover_value, part_area_excluded, part_area_included }
+    pub fn new(material_cost: u64, leftover_value: f32, part_area_excluded: u64, part_area_included: u64, part_value_excluded: u64, n_cuts: u64, cut_length: u64) -> Self {
+        Self { material_cost, leftover_value, part_area_excluded, part_area_included, part_value_excluded, n_cuts, cut_length, n_patterns: 0, order_spread: 0 }
     }
 
     pub fn add(mut self, other: &Cost) -> Self {
@@ -29,6 +41,11 @@ impl Cost {
         self.leftover_value += other.leftover_value;
         self.part_area_excluded += other.part_area_excluded;
         self.part_area_included += other.part_area_included;
+        self.part_value_excluded += other.part_value_excluded;
+        self.n_cuts += other.n_cuts;
+        self.cut_length += other.cut_length;
+        self.n_patterns += other.n_patterns;
+        self.order_spread += other.order_spread;
         self
     }
 
@@ -37,6 +54,11 @@ impl Cost {
         self.leftover_value -= other.leftover_value;
         self.part_area_excluded -= other.part_area_excluded;
         self.part_area_included -= other.part_area_included;
+        self.part_value_excluded -= other.part_value_excluded;
+        self.n_cuts -= other.n_cuts;
+        self.cut_length -= other.cut_length;
+        self.n_patterns -= other.n_patterns;
+        self.order_spread -= other.order_spread;
         self
     }
 
@@ -53,6 +75,17 @@ impl Cost {
         self.material_cost += material_cost;
         self
     }
+
+    pub fn add_order_spread(mut self, order_spread: u64) -> Self {
+        self.order_spread += order_spread;
+        self
+    }
+
+    pub fn add_cuts(mut self, n_cuts: u64, cut_length: u64) -> Self {
+        self.n_cuts += n_cuts;
+        self.cut_length += cut_length;
+        self
+    }
 }
 
 impl Add for Cost {
@@ -64,6 +97,11 @@ impl Add for Cost {
             leftover_value: self.leftover_value + rhs.leftover_value,
             part_area_excluded: self.part_area_excluded + rhs.part_area_excluded,
             part_area_included: self.part_area_included + rhs.part_area_included,
+            part_value_excluded: self.part_value_excluded + rhs.part_value_excluded,
+            n_cuts: self.n_cuts + rhs.n_cuts,
+            cut_length: self.cut_length + rhs.cut_length,
+            n_patterns: self.n_patterns + rhs.n_patterns,
+            order_spread: self.order_spread + rhs.order_spread,
         }
     }
 }
@@ -77,12 +115,17 @@ impl Sub for Cost {
             leftover_value: self.leftover_value - rhs.leftover_value,
             part_area_excluded: self.part_area_excluded - rhs.part_area_excluded,
             part_area_included: self.part_area_included - rhs.part_area_included,
+            part_value_excluded: self.part_value_excluded - rhs.part_value_excluded,
+            n_cuts: self.n_cuts - rhs.n_cuts,
+            cut_length: self.cut_length - rhs.cut_length,
+            n_patterns: self.n_patterns - rhs.n_patterns,
+            order_spread: self.order_spread - rhs.order_spread,
         }
     }
 }
 
 impl Sum for Cost {
     fn sum<I: Iterator<Item=Self>>(iter: I) -> Self {
-        iter.fold(Self::new(0, 0.0, 0, 0), |acc, cost| acc + cost)
+        iter.fold(Self::empty(), |acc, cost| acc + cost)
     }
 }
\ No newline at end of file
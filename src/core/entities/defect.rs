@@ -0,0 +1,213 @@
+use itertools::Itertools;
+
+use crate::core::entities::sheettype::SheetType;
+use crate::core::insertion::node_blueprint::NodeBlueprint;
+use crate::core::orientation::Orientation;
+
+/// A region of a sheet (knot, hole, stain...) which cannot be part of any item.
+/// Its coordinates are relative to the top left corner of the sheet.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defect {
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+/// Generates the fixed structure of the layouts of a sheettype with defects.
+/// The usable area of the sheet is cut into pieces until every defect is isolated in a fixed piece of waste.
+/// All pieces without defects are regular empty nodes, in which parts can be inserted.
+/// As their position on the sheet matters, the fixed nodes can never be removed and the order of their children is preserved.
+pub fn generate_defect_structure(sheettype: &SheetType, first_cut_orientation: Orientation) -> NodeBlueprint {
+    let top_node = generate_top_node(sheettype, first_cut_orientation);
+    assert!(!top_node.children().is_empty(), "sheettype {} is entirely covered by defects", sheettype.id());
+    top_node
+}
+
+/// Whether the defects (and the pieces between them which are too thin to be used) leave no usable area on the sheet.
+/// Such sheettypes cannot be used at all, see parser::generate_instance.
+pub fn covers_usable_area(sheettype: &SheetType) -> bool {
+    !sheettype.defects().is_empty() && generate_top_node(sheettype, Orientation::Horizontal).children().is_empty()
+}
+
+fn generate_top_node(sheettype: &SheetType, first_cut_orientation: Orientation) -> NodeBlueprint {
+    let kerf = sheettype.kerf();
+    let trim = sheettype.trim();
+    let offset_x = if trim.left > 0 { trim.left + kerf } else { 0 };
+    let offset_y = if trim.top > 0 { trim.top + kerf } else { 0 };
+
+    //All rectangles are expressed relative to the usable area and include the kerf of the cut after them (see Node)
+    let area = Rect {
+        x0: 0,
+        y0: 0,
+        x1: sheettype.usable_width() + kerf,
+        y1: sheettype.usable_height() + kerf,
+    };
+    let defects = sheettype.defects().iter()
+        .map(|d| Rect {
+            x0: d.x.saturating_sub(offset_x),
+            y0: d.y.saturating_sub(offset_y),
+            x1: (d.x + d.width + kerf).saturating_sub(offset_x),
+            y1: (d.y + d.height + kerf).saturating_sub(offset_y),
+        })
+        .filter_map(|d| d.intersection(&area, kerf))
+        .collect_vec();
+
+    generate_node(area, defects, first_cut_orientation, 0, sheettype)
+}
+
+fn generate_node(area: Rect, defects: Vec<Rect>, next_cut_orient: Orientation, level: u8, sheettype: &SheetType) -> NodeBlueprint {
+    let (width, height) = (area.x1 - area.x0, area.y1 - area.y0);
+
+    if defects.is_empty() {
+        return NodeBlueprint::new(width, height, None, next_cut_orient);
+    }
+
+    let kerf = sheettype.kerf();
+    let defect_intervals = merged_intervals(&area, &defects, next_cut_orient, sheettype);
+    let fully_covered = |o: Orientation| merged_intervals(&area, &defects, o, sheettype) == [area.interval(o)];
+
+    //Pieces which contain a defect and cannot be cut any further (or are too thin to leave usable pieces next to it) are waste
+    if level >= sheettype.max_stages() || sheettype.is_unmachinable_waste(width, height)
+        || (fully_covered(next_cut_orient) && fully_covered(next_cut_orient.rotate())) {
+        return NodeBlueprint::new_fixed(width, height, next_cut_orient);
+    }
+
+    //Cut the area into alternating pieces with and without defects
+    let (start, end) = area.interval(next_cut_orient);
+    let mut cuts = vec![start];
+    for (d0, d1) in defect_intervals {
+        cuts.extend([d0, d1]);
+    }
+    cuts.push(end);
+
+    let mut node = NodeBlueprint::new_fixed(width, height, next_cut_orient);
+    for (&s, &e) in cuts.iter().tuple_windows() {
+        if s < e {
+            let piece = area.slice(next_cut_orient, s, e);
+            let piece_defects = defects.iter().filter_map(|d| d.intersection(&piece, kerf)).collect_vec();
+            node.add_child(generate_node(piece, piece_defects, next_cut_orient.rotate(), level + 1, sheettype));
+        }
+    }
+    node
+}
+
+/// The projections of the defects on the axis divided by cuts of the given orientation, merged where they overlap.
+/// Gaps which would be lost entirely to the kerf, or which are too thin to be cut, are added to the neighbouring defects.
+fn merged_intervals(area: &Rect, defects: &[Rect], orientation: Orientation, sheettype: &SheetType) -> Vec<(u64, u64)> {
+    let (start, end) = area.interval(orientation);
+    let absorbed = |gap: u64| gap <= sheettype.kerf() || sheettype.is_unmachinable_waste(gap, gap);
+    let mut intervals: Vec<(u64, u64)> = vec![];
+    for (d0, d1) in defects.iter().map(|d| d.interval(orientation)).sorted() {
+        match intervals.last_mut() {
+            Some(last) if d0 <= last.1 || absorbed(d0 - last.1) => last.1 = u64::max(last.1, d1),
+            _ => intervals.push((d0, d1)),
+        }
+    }
+    if let Some(first) = intervals.first_mut() && absorbed(first.0 - start) {
+        first.0 = start;
+    }
+    if let Some(last) = intervals.last_mut() && absorbed(end - last.1) {
+        last.1 = end;
+    }
+    intervals
+}
+
+/// The rectangle [x0, x1) x [y0, y1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x0: u64,
+    y0: u64,
+    x1: u64,
+    y1: u64,
+}
+
+impl Rect {
+    /// Intersections which are not larger than the kerf in both dimensions contain no material and are discarded
+    fn intersection(&self, other: &Rect, kerf: u64) -> Option<Rect> {
+        let intersection = Rect {
+            x0: u64::max(self.x0, other.x0),
+            y0: u64::max(self.y0, other.y0),
+            x1: u64::min(self.x1, other.x1),
+            y1: u64::min(self.y1, other.y1),
+        };
+        match intersection.x1 > intersection.x0 + kerf && intersection.y1 > intersection.y0 + kerf {
+            true => Some(intersection),
+            false => None,
+        }
+    }
+
+    /// The interval of the rectangle which is divided by cuts of the given orientation
+    fn interval(&self, orientation: Orientation) -> (u64, u64) {
+        match orientation {
+            Orientation::Horizontal => (self.y0, self.y1),
+            Orientation::Vertical => (self.x0, self.x1),
+        }
+    }
+
+    fn slice(&self, orientation: Orientation, start: u64, end: u64) -> Rect {
+        match orientation {
+            Orientation::Horizontal => Rect { y0: start, y1: end, ..*self },
+            Orientation::Vertical => Rect { x0: start, x1: end, ..*self },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::entities::sheettype::TrimMargins;
+
+    fn sheettype(kerf: u64, defects: &[(u64, u64, u64, u64)]) -> SheetType {
+        SheetType::new(0, 10, 10, 100, None, u8::MAX, kerf)
+            .with_defects(defects.iter().map(|&(x, y, width, height)| Defect { x, y, width, height }).collect())
+    }
+
+    /// The width (kerf included) of the pieces after the first stage of vertical cuts, and whether they contain a defect
+    fn first_stage(sheettype: &SheetType) -> Vec<(u64, bool)> {
+        generate_defect_structure(sheettype, Orientation::Vertical).children().iter()
+            .map(|c| (c.width(), c.is_fixed()))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_defects() {
+        let sheettype = sheettype(0, &[(2, 2, 3, 3), (4, 4, 3, 3)]);
+        assert_eq!(first_stage(&sheettype), vec![(2, false), (5, true), (3, false)]);
+
+        //The piece with both defects is cut around their combined extent in the next stage
+        let top_node = generate_defect_structure(&sheettype, Orientation::Vertical);
+        let heights = top_node.children()[1].children().iter().map(|c| (c.height(), c.is_fixed())).collect_vec();
+        assert_eq!(heights, vec![(2, false), (5, true), (3, false)]);
+    }
+
+    #[test]
+    fn defects_next_to_kerf() {
+        //The single unit of material between the defects is lost to the kerf of the cut between them
+        assert_eq!(first_stage(&sheettype(1, &[(2, 3, 2, 2), (5, 3, 2, 2)])), vec![(2, false), (6, true), (3, false)]);
+        //Two units leave a piece between them, the unit between the second defect and the edge is lost to the kerf
+        assert_eq!(first_stage(&sheettype(1, &[(2, 3, 2, 2), (7, 3, 2, 2)])), vec![(2, false), (3, true), (2, false), (4, true)]);
+    }
+
+    #[test]
+    fn defects_next_to_trim() {
+        let trimmed = |defects| SheetType::new(0, 12, 10, 100, None, u8::MAX, 1)
+            .with_trim(TrimMargins { left: 1, ..TrimMargins::default() })
+            .with_defects(defects);
+        //The usable area starts after the trim strip and the kerf of its cut
+        assert_eq!(first_stage(&trimmed(vec![Defect { x: 2, y: 3, width: 2, height: 2 }])), vec![(3, true), (8, false)]);
+        //Only the part of a defect within the usable area is isolated
+        assert_eq!(first_stage(&trimmed(vec![Defect { x: 0, y: 3, width: 3, height: 2 }])), vec![(2, true), (9, false)]);
+    }
+
+    #[test]
+    fn covered_usable_area() {
+        assert!(!covers_usable_area(&sheettype(0, &[])));
+        assert!(!covers_usable_area(&sheettype(0, &[(2, 2, 3, 3)])));
+        assert!(covers_usable_area(&sheettype(0, &[(0, 0, 10, 10)])));
+        assert!(covers_usable_area(&sheettype(0, &[(0, 0, 6, 10), (5, 0, 5, 10)])));
+        //The remaining strip is too thin to be cut
+        assert!(covers_usable_area(&sheettype(0, &[(0, 0, 9, 10)]).with_min_waste_dimension(2)));
+    }
+}
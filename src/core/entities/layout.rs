@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use generational_arena::{Arena, Index};
 use itertools::Itertools;
 use crate::core::{cost::Cost, insertion::insertion_blueprint::InsertionBlueprint};
+use crate::core::entities::defect;
 use crate::core::entities::node::Node;
-use crate::core::insertion::node_blueprint::NodeBlueprint;
+use crate::core::insertion::node_blueprint::{NodeBlueprint, PatternKey};
 use crate::core::orientation::Orientation;
 use crate::optimization::instance::Instance;
 use crate::optimization::rr::cache_updates::IOCUpdates;
@@ -17,14 +21,23 @@ pub struct Layout<'a> {
     nodes: Arena<Node<'a>>,
     top_node_i: Index,
     cached_cost: Option<Cost>,
+    part_counts: HashMap<usize, usize>, //number of included parts per parttype id
+    n_parts: usize,
+    order_counts: HashMap<usize, usize>, //number of included parts per order
     cached_usage: Option<f64>,
+    cached_pattern_key: Option<Arc<PatternKey>>,
     sorted_empty_nodes: Vec<Index>, //sorted by descending area
 }
 
 impl<'a> Layout<'a> {
     pub fn new(id: usize, sheettype: &'a SheetType, first_cut_orientation: Orientation) -> Self {
+        if !sheettype.defects().is_empty() {
+            return Layout::new_with_defects(id, sheettype, first_cut_orientation);
+        }
+
+        let (width, height) = (sheettype.usable_width() + sheettype.kerf(), sheettype.usable_height() + sheettype.kerf());
         let mut nodes = Arena::new();
-        let top_node = Node::new(0, sheettype.width(), sheettype.height(), first_cut_orientation, None);
+        let top_node = Node::new(0, width, height, first_cut_orientation, None);
         let top_node_i = nodes.insert(top_node);
 
         let mut layout = Self {
@@ -33,13 +46,87 @@ impl<'a> Layout<'a> {
             nodes,
             top_node_i,
             cached_cost: None,
+            part_counts: HashMap::new(),
+            n_parts: 0,
+            order_counts: HashMap::new(),
             cached_usage: None,
+            cached_pattern_key: None,
             sorted_empty_nodes: vec![],
         };
 
         //The top node cannot be modified, so we register a placeholder node to be able to insert parts
-        let placeholder_node = Node::new(1, sheettype.width(), sheettype.height(), first_cut_orientation.rotate(), None);
-        layout.register_node(placeholder_node, top_node_i, true);
+        let placeholder_node = Node::new(1, width, height, first_cut_orientation.rotate(), None);
+        layout.register_node(placeholder_node, top_node_i, None, true);
+
+        layout
+    }
+
+    /// The defects are isolated by a fixed structure of nodes, parts can be inserted in the empty nodes around them
+    fn new_with_defects(id: usize, sheettype: &'a SheetType, first_cut_orientation: Orientation) -> Self {
+        let top_node_bp = defect::generate_defect_structure(sheettype, first_cut_orientation);
+
+        let mut nodes = Arena::new();
+        let top_node = Node::new_fixed(0, top_node_bp.width(), top_node_bp.height(), first_cut_orientation);
+        let top_node_i = nodes.insert(top_node);
+
+        let mut layout = Self {
+            id,
+            sheettype,
+            nodes,
+            top_node_i,
+            cached_cost: None,
+            part_counts: HashMap::new(),
+            n_parts: 0,
+            order_counts: HashMap::new(),
+            cached_usage: None,
+            cached_pattern_key: None,
+            sorted_empty_nodes: vec![],
+        };
+
+        let mut all_created_nodes = vec![];
+        for child_blueprint in top_node_bp.children() {
+            layout.implement_fixed_node_blueprint(top_node_i, child_blueprint, &mut all_created_nodes);
+        }
+
+        debug_assert!(assertions::children_nodes_fit(&top_node_i, &layout.nodes));
+        layout
+    }
+
+    /// Rebuilds a layout from a blueprint of its top node, e.g. to restore a previously generated solution
+    pub fn from_blueprint(id: usize, sheettype: &'a SheetType, top_node_bp: &NodeBlueprint, instance: &'a Instance) -> Self {
+        let (width, height) = (sheettype.usable_width() + sheettype.kerf(), sheettype.usable_height() + sheettype.kerf());
+        assert!(top_node_bp.width() == width && top_node_bp.height() == height, "top node does not match dimensions of sheettype {}", sheettype.id());
+        assert!(!top_node_bp.children().is_empty(), "top node of a layout needs to have children");
+        assert!(sheettype.fixed_first_cut_orientation().is_none_or(|o| o == top_node_bp.next_cut_orient()), "top node does not match the first cut orientation of sheettype {}", sheettype.id());
+
+        let mut nodes = Arena::new();
+        let top_node = match top_node_bp.is_fixed() {
+            true => Node::new_fixed(0, width, height, top_node_bp.next_cut_orient()),
+            false => Node::new(0, width, height, top_node_bp.next_cut_orient(), None),
+        };
+        let top_node_i = nodes.insert(top_node);
+
+        let mut layout = Self {
+            id,
+            sheettype,
+            nodes,
+            top_node_i,
+            cached_cost: None,
+            part_counts: HashMap::new(),
+            n_parts: 0,
+            order_counts: HashMap::new(),
+            cached_usage: None,
+            cached_pattern_key: None,
+            sorted_empty_nodes: vec![],
+        };
+
+        let mut all_created_nodes = vec![];
+        for child_blueprint in top_node_bp.children() {
+            layout.implement_node_blueprint(top_node_i, None, child_blueprint, instance, &mut all_created_nodes);
+        }
+
+        debug_assert!(assertions::children_nodes_fit(&top_node_i, &layout.nodes));
+        debug_assert!(assertions::node_arena_valid(&layout.nodes, &layout.top_node_i));
 
         layout
     }
@@ -54,37 +141,62 @@ impl<'a> Layout<'a> {
     pub fn implement_insertion_blueprint(&mut self, blueprint: &InsertionBlueprint<'a>, instance: &'a Instance, updates: &mut IOCUpdates) {
         let original = *blueprint.original_node_index();
         let parent = self.nodes[original].parent().expect("original node has no parent");
+        let position = self.nodes[parent].children().iter().position(|c| *c == original).expect("original node not found");
 
         //unregister the original node
         self.unregister_node(original, &mut None);
         updates.add_removed(original);
 
-        //create and register the replacements
+        //create and register the replacements, in place of the original node
         let mut all_created_nodes = vec![];
-        for replacement in blueprint.replacements() {
-            self.implement_node_blueprint(parent, replacement, instance, &mut all_created_nodes);
+        for (i, replacement) in blueprint.replacements().iter().enumerate() {
+            self.implement_node_blueprint(parent, Some(position + i), replacement, instance, &mut all_created_nodes);
         }
         updates.extend_new(all_created_nodes);
 
         debug_assert!(assertions::children_nodes_fit(&parent, &self.nodes), "{:#?}", blueprint);
         debug_assert!(assertions::node_arena_valid(&self.nodes, &self.top_node_i));
+        debug_assert!(assertions::no_unmachinable_waste(&self.nodes, self.sheettype), "{:#?}", blueprint);
         debug_assert!(assertions::cached_sorted_empty_nodes_correct(&self.nodes(), &self.sorted_empty_nodes), "{:#?}", self.sorted_empty_nodes.iter().map(|n| &self.nodes[*n]).collect_vec());
     }
 
-    fn implement_node_blueprint(&mut self, parent: Index, blueprint: &NodeBlueprint, instance: &'a Instance, new_nodes: &mut Vec<Index>) {
+    fn implement_node_blueprint(&mut self, parent: Index, position: Option<usize>, blueprint: &NodeBlueprint, instance: &'a Instance, new_nodes: &mut Vec<Index>) {
         let parttype = blueprint.parttype_id().map(|id| instance.get_parttype(id));
+        let level = self.nodes[parent].level() + 1;
 
-        let node = Node::new(self.nodes[parent].level() + 1, blueprint.width(), blueprint.height(), blueprint.next_cut_orient(), parttype);
-        let node_index = self.register_node(node, parent, blueprint.is_empty());
+        let node = match blueprint.is_fixed() {
+            true => Node::new_fixed(level, blueprint.width(), blueprint.height(), blueprint.next_cut_orient()),
+            false => Node::new(level, blueprint.width(), blueprint.height(), blueprint.next_cut_orient(), parttype),
+        };
+        let node_index = self.register_node(node, parent, position, blueprint.is_empty());
+
+        new_nodes.push(node_index);
+
+        for child_blueprint in blueprint.children() {
+            self.implement_node_blueprint(node_index, None, child_blueprint, instance, new_nodes);
+        }
+    }
+
+    /// A freshly generated fixed structure contains no parts, only empty nodes
+    fn implement_fixed_node_blueprint(&mut self, parent: Index, blueprint: &NodeBlueprint, new_nodes: &mut Vec<Index>) {
+        debug_assert!(blueprint.is_fixed() || blueprint.is_empty());
+        let level = self.nodes[parent].level() + 1;
+        let node = match blueprint.is_fixed() {
+            true => Node::new_fixed(level, blueprint.width(), blueprint.height(), blueprint.next_cut_orient()),
+            false => Node::new(level, blueprint.width(), blueprint.height(), blueprint.next_cut_orient(), None),
+        };
+        let node_index = self.register_node(node, parent, None, blueprint.is_empty());
 
         new_nodes.push(node_index);
 
         for child_blueprint in blueprint.children() {
-            self.implement_node_blueprint(node_index, child_blueprint, instance, new_nodes);
+            self.implement_fixed_node_blueprint(node_index, child_blueprint, new_nodes);
         }
     }
 
-    pub fn remove_node(&mut self, node_index: Index) -> Vec<usize>{
+    /// Returns the ids of the removed parts.
+    /// None if the node cannot be removed without leaving unmachinable waste behind, in which case nothing is changed.
+    pub fn remove_node(&mut self, node_index: Index) -> Option<Vec<usize>> {
         /*®
            Scenario 1: Empty node present + other child(ren)
             -> expand existing waste piece
@@ -119,49 +231,77 @@ impl<'a> Layout<'a> {
 
         let parent_node_index = self.nodes[node_index].parent().expect("Cannot remove a node without a parent");
         let parent_node = &self.nodes[parent_node_index];
+        let position = parent_node.children().iter().position(|c| *c == node_index).expect("node not found in parent");
+
+        //Check if there is an empty_node present.
+        //The children of fixed nodes keep their position, so they can only be merged with adjacent empty nodes.
+        let empty_nodes = match parent_node.is_fixed() {
+            false => parent_node.children().iter().find(|c| self.nodes[**c].is_empty()).into_iter().copied().collect_vec(),
+            true => [position.checked_sub(1), Some(position + 1)].into_iter().flatten()
+                .filter_map(|p| parent_node.children().get(p))
+                .filter(|c| self.nodes[**c].is_empty())
+                .copied().collect_vec(),
+        };
 
-        //Check if there is an empty_node present
-        let empty_node = parent_node.children().iter().find(|c| { self.nodes[**c].is_empty()});
+        //The removal should not leave a piece of waste which is too thin to be cut, in that case the entire parent is removed instead.
+        //Fixed parents and the top node cannot be removed, the caller needs to remove the entire layout instead.
+        let merged_nodes = empty_nodes.iter().chain([&node_index]).map(|n| &self.nodes[*n]);
+        let (merged_width, merged_height) = match parent_node.next_cut_orient() {
+            Orientation::Horizontal => (self.nodes[node_index].width(), merged_nodes.map(|n| n.height()).sum()),
+            Orientation::Vertical => (merged_nodes.map(|n| n.width()).sum(), self.nodes[node_index].height()),
+        };
+        if self.sheettype.is_unmachinable_waste(merged_width, merged_height) {
+            return match parent_node.is_fixed() || parent_node.parent().is_none() {
+                true => None,
+                false => self.remove_node(parent_node_index),
+            };
+        }
 
         let mut removed_parts = Some(vec![]);
 
-        match empty_node {
-            Some(&empty_node_index) => {
+        match empty_nodes.is_empty() {
+            false => {
                 //Scenario 1 and 3
-                if parent_node.children().len() > 1 || parent_node.parent().is_none() {
+                if parent_node.children().len() > empty_nodes.len() || parent_node.parent().is_none() {
                     //Scenario 1 (also do this when the parent node is the root)
-                    //Two children are merged into one
+                    //The children are merged into one
 
                     let node = &self.nodes[node_index];
-                    let empty_node = &self.nodes[empty_node_index];
                     let replacement_node = match parent_node.next_cut_orient() {
                         Orientation::Horizontal => {
-                            let new_height = empty_node.height() + node.height();
+                            let new_height = empty_nodes.iter().map(|e| self.nodes[*e].height()).sum::<u64>() + node.height();
                             Node::new(node.level(), node.width(), new_height, node.next_cut_orient(), None)
                         }
                         Orientation::Vertical => {
-                            let new_width = empty_node.width() + node.width();
+                            let new_width = empty_nodes.iter().map(|e| self.nodes[*e].width()).sum::<u64>() + node.width();
                             Node::new(node.level(), new_width, node.height(), node.next_cut_orient(), None)
                         }
                     };
-
-                    //Replace the empty node and the node to be removed with a enlarged empty node
-                    self.unregister_node(empty_node_index, &mut removed_parts);
+                    let merged_position = empty_nodes.iter()
+                        .map(|e| self.nodes[parent_node_index].children().iter().position(|c| c == e).unwrap())
+                        .chain([position])
+                        .min().unwrap();
+
+                    //Replace the empty nodes and the node to be removed with a enlarged empty node
+                    for empty_node_index in empty_nodes {
+                        self.unregister_node(empty_node_index, &mut removed_parts);
+                    }
                     self.unregister_node(node_index, &mut removed_parts);
-                    self.register_node(replacement_node, parent_node_index, true);
+                    self.register_node(replacement_node, parent_node_index, Some(merged_position), true);
                 } else {
                     //Scenario 3: replace the parent with an empty node
-                    let grandparent_index = parent_node.parent().expect("grandparent node needs to be present").clone();
+                    let grandparent_index = parent_node.parent().expect("grandparent node needs to be present");
+                    let parent_position = self.nodes[grandparent_index].children().iter().position(|c| *c == parent_node_index).unwrap();
 
                     //create empty parent
                     let empty_parent_node = Node::new(parent_node.level(), parent_node.width(), parent_node.height(), parent_node.next_cut_orient(), None);
 
                     //replace
                     self.unregister_node(parent_node_index, &mut removed_parts);
-                    self.register_node(empty_parent_node, grandparent_index, true);
+                    self.register_node(empty_parent_node, grandparent_index, Some(parent_position), true);
                 }
             }
-            None => {
+            true => {
                 //Scenario 2: convert the node itself into an empty node
 
                 //create empty replacement node
@@ -170,32 +310,42 @@ impl<'a> Layout<'a> {
 
                 //replace
                 self.unregister_node(node_index, &mut removed_parts);
-                self.register_node(replacement_node, parent_node_index, true);
+                self.register_node(replacement_node, parent_node_index, Some(position), true);
             }
         }
 
         debug_assert!(assertions::node_arena_valid(&self.nodes, &self.top_node_i));
         debug_assert!(assertions::cached_sorted_empty_nodes_correct(&self.nodes(), &self.sorted_empty_nodes), "{:#?}", self.sorted_empty_nodes.iter().map(|n| &self.nodes[*n]).collect_vec());
+        debug_assert!(assertions::no_unmachinable_waste(&self.nodes, self.sheettype));
 
-        removed_parts.unwrap()
+        removed_parts
     }
 
     fn invalidate_caches(&mut self) {
         self.cached_cost = None;
         self.cached_usage = None;
+        self.cached_pattern_key = None;
     }
 
     fn calculate_cost(&self) -> Cost {
-        let material_cost = Cost::empty().add_material_cost(self.sheettype.value());
+        let (n_trim_cuts, trim_cut_length) = self.sheettype.trim_cuts();
+        let material_cost = Cost::empty()
+            .add_material_cost(self.sheettype.value())
+            .add_cuts(n_trim_cuts, trim_cut_length)
+            .add_order_spread(self.n_included_orders() as u64);
         self.nodes.iter()
-            .map(|(_, node)| node.calculate_cost())
+            .map(|(_, node)| {
+                //The children of a node are separated by one cut less than there are children
+                let n_cuts = node.children().iter().filter(|c| self.nodes[**c].area() > 0).count().saturating_sub(1) as u64;
+                node.calculate_cost(self.sheettype).add_cuts(n_cuts, n_cuts * node.cut_length(self.sheettype.kerf()))
+            })
             .fold(material_cost, |acc, cost| acc.add(&cost))
     }
 
     fn calculate_usage(&self) -> f64 {
         let used_area = self.nodes.iter().map(|(_, node)| {
             match node.parttype(){
-                Some(_) => node.area(),
+                Some(parttype) => parttype.area(),
                 None => 0
             }
         }).sum::<u64>();
@@ -203,7 +353,8 @@ impl<'a> Layout<'a> {
         used_area as f64 / self.sheettype.area() as f64
     }
 
-    fn register_node(&mut self, node: Node<'a>, parent: Index, is_empty: bool) -> Index {
+    /// Registers the node as a child of the parent, at the given position among its children (or after them)
+    fn register_node(&mut self, node: Node<'a>, parent: Index, position: Option<usize>, is_empty: bool) -> Index {
         self.invalidate_caches();
 
         if let Some(parttype) = node.parttype() {
@@ -233,7 +384,10 @@ impl<'a> Layout<'a> {
 
         //Configure relationship between node and parent
         self.nodes[node_index].set_parent(parent);
-        self.nodes[parent].add_child(node_index);
+        match position {
+            Some(position) => self.nodes[parent].insert_child(position, node_index),
+            None => self.nodes[parent].add_child(node_index),
+        }
 
         debug_assert!(assertions::node_arena_valid(&self.nodes, &self.top_node_i));
         node_index
@@ -294,12 +448,30 @@ impl<'a> Layout<'a> {
         debug_assert!(assertions::node_arena_valid(&self.nodes, &self.top_node_i));
     }
 
-    fn register_part(&mut self, _parttype: &PartType) {
+    fn register_part(&mut self, parttype: &PartType) {
         self.invalidate_caches();
+        *self.part_counts.entry(parttype.id()).or_insert(0) += 1;
+        self.n_parts += 1;
+        if let Some(order) = parttype.order() {
+            *self.order_counts.entry(order).or_insert(0) += 1;
+        }
     }
 
-    fn unregister_part(&mut self, _parttype: &PartType) {
+    fn unregister_part(&mut self, parttype: &PartType) {
         self.invalidate_caches();
+        let count = self.part_counts.get_mut(&parttype.id()).expect("parttype not registered");
+        *count -= 1;
+        if *count == 0 {
+            self.part_counts.remove(&parttype.id());
+        }
+        self.n_parts -= 1;
+        if let Some(order) = parttype.order() {
+            let count = self.order_counts.get_mut(&order).expect("order not registered");
+            *count -= 1;
+            if *count == 0 {
+                self.order_counts.remove(&order);
+            }
+        }
     }
 
     pub fn get_included_parts(&self) -> Vec<usize> {
@@ -309,8 +481,30 @@ impl<'a> Layout<'a> {
             .collect_vec()
     }
 
+    /// Whether another part of this parttype can be included without exceeding the item limits of the sheettype
+    pub fn item_limits_allow(&self, parttype: &PartType) -> bool {
+        let (max_items, max_parttypes) = (self.sheettype.max_items(), self.sheettype.max_parttypes());
+        if max_items == usize::MAX && max_parttypes == usize::MAX {
+            return true;
+        }
+        debug_assert!(self.get_included_parts().into_iter().counts() == self.part_counts);
+        self.n_parts < max_items
+            && (self.part_counts.contains_key(&parttype.id()) || self.part_counts.len() < max_parttypes)
+    }
+
+    /// Whether any part of the given order is included in this layout
+    pub fn contains_order(&self, order: usize) -> bool {
+        self.order_counts.contains_key(&order)
+    }
+
+    /// The number of distinct orders of the parts included in this layout
+    pub fn n_included_orders(&self) -> usize {
+        self.order_counts.len()
+    }
+
+    /// Whether the layout contains no parts, the top node always keeps at least one (empty) child
     pub fn is_empty(&self) -> bool {
-        self.nodes.iter().all(|(_, n)| n.is_empty())
+        self.n_parts == 0
     }
 
     pub fn cost(&mut self, force_recalc: bool) -> Cost {
@@ -363,6 +557,13 @@ impl<'a> Layout<'a> {
         usage
     }
 
+    /// Identifies the cutting pattern, identical layouts (see SendableLayout) have equal keys
+    pub fn pattern_key(&mut self) -> Arc<PatternKey> {
+        self.cached_pattern_key.get_or_insert_with(|| {
+            Arc::new(PatternKey::new(self.sheettype.id(), &NodeBlueprint::from_node(self.top_node_i, &self.nodes)))
+        }).clone()
+    }
+
     pub fn sorted_empty_nodes(&self) -> &Vec<Index> {
         debug_assert!(assertions::node_arena_valid(&self.nodes, &self.top_node_i), "{:#?}", self.sorted_empty_nodes.iter().map(|n| &self.nodes[*n]).collect_vec());
         debug_assert!(assertions::cached_sorted_empty_nodes_correct(&self.nodes(), &self.sorted_empty_nodes), "{:#?}", self.sorted_empty_nodes.iter().map(|n| &self.nodes[*n]).collect_vec());
@@ -371,9 +572,10 @@ impl<'a> Layout<'a> {
     }
 
     pub fn get_removable_nodes(&self) -> Vec<Index> {
-        //All nodes with children or that contain a part are removable
+        //All nodes with children or that contain a part are removable, except for the fixed structure around defects
         self.nodes.iter()
             .filter(|(_, node)| node.parttype().is_some() || !node.children().is_empty())
+            .filter(|(index, node)| !node.is_fixed() || *index == self.top_node_i)
             .map(|(index, _)| index)
             .collect_vec()
     }
@@ -393,4 +595,235 @@ impl<'a> Layout<'a> {
     pub fn id(&self) -> usize {
         self.id
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::insertion::insertion_option::InsertionOption;
+    use crate::core::layout_index::LayoutIndex;
+    use crate::core::entities::sheettype::{StageMode, TrimMargins};
+    use crate::core::rotation::Rotation;
+    use crate::optimization::problem::Problem;
+
+    /// A 10x10 sheettype, cut vertically first
+    fn sheettype(kerf: u64) -> SheetType {
+        SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), u8::MAX, kerf)
+    }
+
+    /// Parttypes without rotation, with a demand of 10
+    fn new_instance(sheettype: SheetType, parttypes: &[(u64, u64)]) -> Instance {
+        let parts = parttypes.iter().enumerate()
+            .map(|(id, &(width, height))| (PartType::new(id, width, height, width * height, Some(Rotation::Default), None), 10))
+            .collect();
+        Instance::new(parts, vec![(sheettype, 10)])
+    }
+
+    fn new_problem(instance: &Instance) -> Problem<'_> {
+        crate::core::leftover_valuator::set_power(2.0);
+        Problem::new(instance, Some(0), 0)
+    }
+
+    /// Inserts a part into the largest empty node of the only layout in which it fits (the layout is opened if necessary),
+    /// using the first blueprint generated for it. Returns the cost of the blueprint, None if the part does not fit.
+    fn insert(problem: &mut Problem, parttype_id: usize) -> Option<Cost> {
+        let parttype = problem.instance().get_parttype(parttype_id);
+        let layout_index = match problem.layouts().iter().next() {
+            Some((index, _)) => LayoutIndex::Existing(index),
+            None => LayoutIndex::Empty(0),
+        };
+        let layout = problem.get_layout(&layout_index);
+        let node_index = *layout.sorted_empty_nodes().iter()
+            .find(|n| layout.nodes()[**n].insertion_possible(parttype, Rotation::Default, layout.sheettype()))?;
+        let blueprint = InsertionOption::new(layout_index, node_index, parttype, Some(Rotation::Default)).generate_blueprints(problem).into_iter().next()?;
+        problem.implement_insertion_blueprint(&blueprint);
+        Some(blueprint.cost().clone())
+    }
+
+    /// Removes the first node which contains a part of the parttype
+    fn remove(problem: &mut Problem, parttype_id: usize) {
+        let (layout_index, layout) = problem.layouts().iter().next().unwrap();
+        let (node_index, _) = layout.nodes().iter().find(|(_, n)| n.parttype().is_some_and(|p| p.id() == parttype_id)).unwrap();
+        problem.remove_node(node_index, LayoutIndex::Existing(layout_index));
+    }
+
+    fn layout<'a, 'b>(problem: &'b mut Problem<'a>) -> &'b mut Layout<'a> {
+        problem.layouts_mut().iter_mut().next().unwrap().1
+    }
+
+    #[test]
+    fn kerf() {
+        let instance = new_instance(sheettype(1), &[(5, 10), (4, 10), (10, 10)]);
+        let mut problem = new_problem(&instance);
+
+        //Every node includes the kerf of the cut after it, so the remaining strip of 5 only holds an item of 4
+        assert!(insert(&mut problem, 0).is_some());
+        assert!(insert(&mut problem, 0).is_none());
+        assert!(insert(&mut problem, 1).is_some());
+        assert_eq!(layout(&mut problem).usage(false), 0.9);
+
+        //After removing the item, its space (and the kerf of its cut) can be used again
+        remove(&mut problem, 1);
+        assert_eq!(layout(&mut problem).usage(false), 0.5);
+        assert!(insert(&mut problem, 1).is_some());
+
+        //The last cut along the edge of the sheet can be left out, so an item the size of the sheet fits
+        let mut problem = new_problem(&instance);
+        assert!(insert(&mut problem, 2).is_some());
+        assert_eq!(layout(&mut problem).usage(false), 1.0);
+    }
+
+    #[test]
+    fn trim() {
+        //Every trim cut removes a kerf in addition to its margin, leaving a usable area of 10x10
+        let trim = TrimMargins { left: 1, right: 1, top: 1, bottom: 1 };
+        let sheettype = SheetType::new(0, 14, 14, 196, Some(Orientation::Vertical), u8::MAX, 1).with_trim(trim);
+        let instance = new_instance(sheettype, &[(11, 10), (10, 11), (10, 10)]);
+        let mut problem = new_problem(&instance);
+
+        assert!(insert(&mut problem, 0).is_none());
+        assert!(insert(&mut problem, 1).is_none());
+        assert!(insert(&mut problem, 2).is_some());
+
+        //The vertical trim cuts span the sheet, the horizontal ones only the width between them
+        let cost = layout(&mut problem).cost(false).clone();
+        assert_eq!((cost.n_cuts, cost.cut_length), (4, 2 * 14 + 2 * 10));
+        assert_eq!(layout(&mut problem).usage(false), 100.0 / 196.0);
+
+        //The layout is removed together with its last item
+        remove(&mut problem, 2);
+        assert!(problem.layouts().is_empty());
+        assert!(insert(&mut problem, 2).is_some());
+    }
+
+    #[test]
+    fn max_stages() {
+        let max_level = |problem: &mut Problem| layout(problem).nodes().iter().map(|(_, n)| n.level()).max().unwrap();
+
+        //A single stage of vertical cuts only produces items of the full height
+        let instance = new_instance(SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 1, 0), &[(5, 5), (5, 10)]);
+        let mut problem = new_problem(&instance);
+        assert!(insert(&mut problem, 0).is_none());
+        assert!(insert(&mut problem, 1).is_some());
+        assert_eq!(max_level(&mut problem), 1);
+
+        let instance = new_instance(SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 2, 0), &[(5, 5)]);
+        let mut problem = new_problem(&instance);
+        assert!(insert(&mut problem, 0).is_some());
+        assert!(insert(&mut problem, 0).is_some());
+        assert_eq!(max_level(&mut problem), 2);
+
+        //With trimming, the item is separated from the waste by an extra cut, which leaves the waste unusable
+        let sheettype = SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 1, 0).with_stage_mode(StageMode::WithTrimming);
+        let instance = new_instance(sheettype, &[(5, 5)]);
+        let mut problem = new_problem(&instance);
+        assert!(insert(&mut problem, 0).is_some());
+        assert!(insert(&mut problem, 0).is_some());
+        assert!(insert(&mut problem, 0).is_none());
+        assert_eq!(max_level(&mut problem), 2);
+        assert_eq!(layout(&mut problem).usage(false), 0.5);
+    }
+
+    #[test]
+    fn first_cut() {
+        let free = SheetType::new(0, 10, 10, 100, None, 1, 0);
+        let instance = new_instance(free, &[]);
+        let orientations = new_problem(&instance).empty_layouts().iter()
+            .map(|l| l.nodes()[*l.top_node_index()].next_cut_orient()).collect_vec();
+        assert_eq!(orientations, [Orientation::Horizontal, Orientation::Vertical]);
+
+        //A fixed first cut only allows layouts which start with cuts of that orientation
+        let fixed = SheetType::new(0, 10, 10, 100, Some(Orientation::Horizontal), 1, 0);
+        let instance = new_instance(fixed, &[(5, 10), (10, 5)]);
+        let mut problem = new_problem(&instance);
+        assert_eq!(problem.empty_layouts().len(), 1);
+        assert!(insert(&mut problem, 0).is_none());
+        assert!(insert(&mut problem, 1).is_some());
+    }
+
+    #[test]
+    fn cut_counting() {
+        //Without a kerf on a 10x10 sheet and with a kerf of 1 on a 11x11 sheet, which both hold four items of 5x5.
+        //First a vertical cut splits the sheet in two strips, which are each cut in half horizontally.
+        //The kerf is not part of the length of a cut.
+        for (kerf, size, cut_length) in [(0, 10, 20), (1, 11, 21)] {
+            let instance = new_instance(SheetType::new(0, size, size, 100, Some(Orientation::Vertical), 2, kerf), &[(5, 5)]);
+            let mut problem = new_problem(&instance);
+            let cuts = |problem: &mut Problem| {
+                let cost = match problem.layouts().is_empty() {
+                    true => problem.empty_layouts()[0].clone().cost(true),
+                    false => layout(problem).cost(false),
+                };
+                (cost.n_cuts, cost.cut_length)
+            };
+
+            //The cuts of a blueprint are the ones added to the layout by implementing it
+            for expected_cuts in [(2, cut_length - 5), (3, cut_length), (3, cut_length), (3, cut_length)] {
+                let cuts_before = cuts(&mut problem);
+                let cost = insert(&mut problem, 0).unwrap();
+                assert_eq!((cuts_before.0 + cost.n_cuts, cuts_before.1 + cost.cut_length), expected_cuts);
+                assert_eq!(cuts(&mut problem), expected_cuts);
+            }
+            assert!(insert(&mut problem, 0).is_none());
+
+            //A removed item leaves waste behind, which is still separated by the same cuts
+            remove(&mut problem, 0);
+            assert_eq!(cuts(&mut problem), (3, cut_length));
+            let cost = layout(&mut problem).cost(true);
+            assert_eq!((cost.n_cuts, cost.cut_length), (3, cut_length));
+        }
+    }
+
+    #[test]
+    fn unmachinable_waste() {
+        let sheettype = SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 2, 0).with_min_waste_dimension(2);
+        let instance = new_instance(sheettype, &[(9, 10), (8, 10), (1, 10)]);
+        let mut problem = new_problem(&instance);
+
+        //Insertions which leave waste thinner than the minimum dimension are rejected
+        assert!(insert(&mut problem, 0).is_none());
+        assert!(insert(&mut problem, 1).is_some());
+        assert!(insert(&mut problem, 2).is_none());
+
+        //Thin items are fine, as long as the waste next to them is not
+        let mut problem = new_problem(&instance);
+        assert!(insert(&mut problem, 2).is_some());
+        assert!(insert(&mut problem, 2).is_some());
+        assert!(insert(&mut problem, 1).is_some());
+        assert_eq!(layout(&mut problem).usage(false), 1.0);
+
+        //Removing one of the thin items would leave a sliver of waste, so the entire layout is removed instead
+        remove(&mut problem, 2);
+        assert!(problem.layouts().is_empty());
+        assert_eq!(problem.parttype_qtys(), &vec![10, 10, 10]);
+    }
+
+    #[test]
+    fn order_spread() {
+        let sheettype = SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 1, 0);
+        let parttypes = [None, Some(0), Some(0), Some(1)].into_iter().enumerate()
+            .map(|(id, order)| (PartType::new(id, 2, 10, 20, Some(Rotation::Default), order), 10))
+            .collect();
+        let instance = Instance::new(parttypes, vec![(sheettype, 10)]);
+        let mut problem = new_problem(&instance);
+        let orders = |problem: &mut Problem| {
+            let layout = layout(problem);
+            ((0..2).filter(|o| layout.contains_order(*o)).collect_vec(), layout.n_included_orders(), layout.cost(false).order_spread)
+        };
+
+        //Only the first part of an order increases the spread
+        assert_eq!(insert(&mut problem, 0).unwrap().order_spread, 0);
+        assert_eq!(insert(&mut problem, 1).unwrap().order_spread, 1);
+        assert_eq!(insert(&mut problem, 2).unwrap().order_spread, 0);
+        assert_eq!(insert(&mut problem, 3).unwrap().order_spread, 1);
+        assert_eq!(orders(&mut problem), (vec![0, 1], 2, 2));
+
+        //The order is only left out once its last part is removed
+        remove(&mut problem, 1);
+        assert_eq!(orders(&mut problem), (vec![0, 1], 2, 2));
+        remove(&mut problem, 2);
+        assert_eq!(orders(&mut problem), (vec![1], 1, 1));
+        assert_eq!(insert(&mut problem, 2).unwrap().order_spread, 1);
+        assert_eq!(orders(&mut problem), (vec![0, 1], 2, 2));
+    }
+}
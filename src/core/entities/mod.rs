@@ -1,4 +1,5 @@
 pub mod layout;
+pub mod defect;
 pub mod node;
 pub mod parttype;
 pub mod sheettype;
@@ -2,10 +2,16 @@ use generational_arena::{Index};
 
 use crate::core::cost::Cost;
 use crate::core::entities::parttype::PartType;
+use crate::core::entities::sheettype::SheetType;
 use crate::core::insertion::node_blueprint::NodeBlueprint;
 use crate::core::leftover_valuator;
 use crate::core::orientation::Orientation;
 use crate::core::rotation::Rotation;
+use crate::core::size::Size;
+
+/// The dimensions of a Node include the kerf of the cut which separates it from its next sibling.
+/// As such, the top node of a layout is one kerf wider and higher than its sheet, and parts occupy one kerf more than their size in both dimensions.
+/// This way the kerf does not need to be accounted for when nodes are split or merged.
 
 #[derive(Debug, Clone)]
 pub struct Node<'a> {
@@ -16,6 +22,7 @@ pub struct Node<'a> {
     parent: Option<Index>,
     parttype: Option<&'a PartType>,
     next_cut_orient: Orientation,
+    fixed: bool,
 }
 
 
@@ -29,6 +36,16 @@ impl<'a> Node<'a> {
             parent: None,
             parttype,
             next_cut_orient,
+            fixed: false,
+        }
+    }
+
+    /// Creates a node of the fixed structure around the defects of a sheet (see defect::generate_defect_structure).
+    /// Fixed nodes without children contain a defect and are waste.
+    pub fn new_fixed(level: u8, width: u64, height: u64, next_cut_orient: Orientation) -> Node<'a> {
+        Node {
+            fixed: true,
+            ..Node::new(level, width, height, next_cut_orient, None)
         }
     }
 
@@ -40,18 +57,20 @@ impl<'a> Node<'a> {
         self.children.push(child);
     }
 
+    pub fn insert_child(&mut self, position: usize, child: Index) {
+        self.children.insert(position, child);
+    }
+
     pub fn remove_child(&mut self, old_child: Index) {
         let old_child_index = self.children.iter().position(|c| *c == old_child).expect("Child not found");
         self.children.remove(old_child_index);
     }
 
-    pub fn generate_insertion_node_blueprints(&self, parttype: &'a PartType, rotation: Rotation, max_level: u8, mut insertion_replacements: Vec<Vec<NodeBlueprint>>) -> Vec<Vec<NodeBlueprint>> {
-        debug_assert!(self.insertion_possible(parttype, rotation));
+    pub fn generate_insertion_node_blueprints(&self, parttype: &'a PartType, rotation: Rotation, sheettype: &SheetType, mut insertion_replacements: Vec<Vec<NodeBlueprint>>) -> Vec<Vec<NodeBlueprint>> {
+        debug_assert!(self.insertion_possible(parttype, rotation, sheettype));
 
-        let part_size = match rotation {
-            Rotation::Default => parttype.size(),
-            Rotation::Rotated => parttype.rotated_size()
-        };
+        let max_level = sheettype.max_node_level();
+        let part_size = &Node::occupied_size(parttype, rotation, sheettype);
 
         /*
              Scenario 1: Part fits exactly into Node
@@ -82,7 +101,7 @@ impl<'a> Node<'a> {
             let part_node = NodeBlueprint::new(part_size.width(), self.height, Some(parttype), self.next_cut_orient);
             let remainder_node = NodeBlueprint::new(remainder_width, self.height, None, self.next_cut_orient);
 
-            insertion_replacements.push(vec![part_node, remainder_node]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![part_node, remainder_node]);
             return insertion_replacements;
         }
         if self.next_cut_orient == Orientation::Vertical && self.width == part_size.width() {
@@ -90,7 +109,7 @@ impl<'a> Node<'a> {
             let part_node = NodeBlueprint::new(self.width, part_size.height(), Some(parttype), self.next_cut_orient);
             let remainder_node = NodeBlueprint::new(self.width, remainder_height, None, self.next_cut_orient);
 
-            insertion_replacements.push(vec![part_node, remainder_node]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![part_node, remainder_node]);
             return insertion_replacements;
         }
 
@@ -114,7 +133,7 @@ impl<'a> Node<'a> {
             copy.add_child(part_node);
             copy.add_child(remainder_node);
 
-            insertion_replacements.push(vec![copy]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![copy]);
             return insertion_replacements;
         }
 
@@ -129,7 +148,7 @@ impl<'a> Node<'a> {
             copy.add_child(part_node);
             copy.add_child(remainder_node);
 
-            insertion_replacements.push(vec![copy]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![copy]);
 
             return insertion_replacements;
         }
@@ -159,7 +178,7 @@ impl<'a> Node<'a> {
             part_node_parent.add_child(part_node);
             part_node_parent.add_child(remainder_node_bottom);
 
-            insertion_replacements.push(vec![part_node_parent, remainder_node_top]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![part_node_parent, remainder_node_top]);
         }
 
         if self.next_cut_orient == Orientation::Vertical && self.level < max_level {
@@ -174,7 +193,7 @@ impl<'a> Node<'a> {
             part_node_parent.add_child(part_node);
             part_node_parent.add_child(remainder_node_bottom);
 
-            insertion_replacements.push(vec![part_node_parent, remainder_node_top]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![part_node_parent, remainder_node_top]);
         }
 
         /*
@@ -204,7 +223,7 @@ impl<'a> Node<'a> {
             copy.add_child(part_node_parent);
             copy.add_child(remainder_node_top);
 
-            insertion_replacements.push(vec![copy]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![copy]);
         }
 
         if self.next_cut_orient == Orientation::Vertical && self.level + 1 < max_level {
@@ -225,34 +244,64 @@ impl<'a> Node<'a> {
             copy.add_child(part_node_parent);
             copy.add_child(remainder_node_top);
 
-            insertion_replacements.push(vec![copy]);
+            Node::push_replacement(&mut insertion_replacements, sheettype, vec![copy]);
         }
         insertion_replacements
     }
 
-    pub fn insertion_possible(&self, parttype: &PartType, rotation: Rotation) -> bool {
+    /// Replacements which would leave a piece of waste that is too thin to be cut are rejected
+    fn push_replacement(insertion_replacements: &mut Vec<Vec<NodeBlueprint>>, sheettype: &SheetType, replacement: Vec<NodeBlueprint>) {
+        if !replacement.iter().any(|nb| nb.contains_unmachinable_waste(sheettype)) {
+            insertion_replacements.push(replacement);
+        }
+    }
+
+    pub fn insertion_possible(&self, parttype: &PartType, rotation: Rotation, sheettype: &SheetType) -> bool {
         debug_assert!(*parttype.fixed_rotation() == None || *parttype.fixed_rotation() == Some(rotation));
         debug_assert!(self.children.is_empty() && self.parttype.is_none());
 
+        //Nodes created by a trimming cut beyond the last stage are waste
+        if self.level > sheettype.max_stages() {
+            return false;
+        }
+
+        let part_size = Node::occupied_size(parttype, rotation, sheettype);
+
+        self.width >= part_size.width() && self.height >= part_size.height()
+    }
+
+    /// The size a part occupies within a node, including the kerf of the cuts that separate it from its neighbours
+    fn occupied_size(parttype: &PartType, rotation: Rotation, sheettype: &SheetType) -> Size {
         let part_size = match rotation {
             Rotation::Default => parttype.size(),
             Rotation::Rotated => parttype.rotated_size()
         };
-
-        self.width >= part_size.width() && self.height >= part_size.height()
+        Size::new(part_size.width() + sheettype.kerf(), part_size.height() + sheettype.kerf())
     }
 
-    pub fn calculate_cost(&self) -> Cost {
+    pub fn calculate_cost(&self, sheettype: &SheetType) -> Cost {
+        if self.fixed {
+            return Cost::empty(); // fixed nodes without children are waste
+        }
         match (self.parttype, self.children.is_empty()) {
             (Some(_), true) => Cost::empty(), // part-node
             (None, false) => Cost::empty(), // structure-node
-            (None, true) => Cost::empty().add_leftover_value(leftover_valuator::valuate(self.area())), //leftover node
+            (None, true) if self.level > sheettype.max_stages() => Cost::empty(), // trimmed waste
+            (None, true) => Cost::empty().add_leftover_value(leftover_valuator::valuate(self.width.saturating_sub(sheettype.kerf()), self.height.saturating_sub(sheettype.kerf()))), //leftover node
             (Some(_), false) => panic!("Parttype set on node with children"),
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.parttype.is_none() && self.children.is_empty()
+        self.parttype.is_none() && self.children.is_empty() && !self.fixed
+    }
+
+    /// The length of a single cut between the children of this node
+    pub fn cut_length(&self, kerf: u64) -> u64 {
+        match self.next_cut_orient {
+            Orientation::Horizontal => self.width.saturating_sub(kerf),
+            Orientation::Vertical => self.height.saturating_sub(kerf),
+        }
     }
 
     pub fn width(&self) -> u64 {
@@ -279,6 +328,9 @@ impl<'a> Node<'a> {
     pub fn level(&self) -> u8 {
         self.level
     }
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
 }
 
 
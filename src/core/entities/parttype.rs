@@ -8,18 +8,22 @@ pub struct PartType {
     id: usize,
     width: u64,
     height: u64,
+    value: u64,
     fixed_rotation: Option<Rotation>,
+    order: Option<usize>,
     size: Size,
     rotated_size: Size,
 }
 
 impl PartType {
-    pub fn new(id: usize, width: u64, height: u64, fixed_rotation: Option<Rotation>) -> PartType {
+    pub fn new(id: usize, width: u64, height: u64, value: u64, fixed_rotation: Option<Rotation>, order: Option<usize>) -> PartType {
         PartType {
             id,
             width,
             height,
+            value,
             fixed_rotation,
+            order,
             size: Size::new(width, height),
             rotated_size: Size::new(height, width),
         }
@@ -37,10 +41,20 @@ impl PartType {
         self.height
     }
 
+    /// The value of a single item of this parttype, maximized in knapsack mode
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
     pub fn fixed_rotation(&self) -> &Option<Rotation> {
         &self.fixed_rotation
     }
 
+    /// The index of the customer order this parttype belongs to, if any
+    pub fn order(&self) -> Option<usize> {
+        self.order
+    }
+
     pub fn size(&self) -> &Size {
         &self.size
     }
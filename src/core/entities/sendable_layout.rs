@@ -1,6 +1,8 @@
+use std::sync::{Arc, OnceLock};
+
 use crate::core::cost::Cost;
 use crate::core::entities::layout::Layout;
-use crate::core::insertion::node_blueprint::NodeBlueprint;
+use crate::core::insertion::node_blueprint::{NodeBlueprint, PatternKey};
 use crate::optimization::instance::Instance;
 
 ///Representation of a layout that can be sent across threads
@@ -11,6 +13,8 @@ pub struct SendableLayout {
     top_node: NodeBlueprint,
     cost: Cost,
     usage: f64,
+    /// Computed on first use, most layouts are never compared
+    pattern_key: OnceLock<Arc<PatternKey>>,
 }
 
 impl SendableLayout {
@@ -20,11 +24,13 @@ impl SendableLayout {
             top_node: NodeBlueprint::from_node(*layout.top_node_index(), layout.nodes()),
             cost: layout.cost_immut(false),
             usage: layout.usage_immut(false),
+            pattern_key: OnceLock::new(),
         }
     }
 
-    pub fn convert_to_layout<'a>(&self, _instance: &'a Instance) -> Layout<'a> {
-        todo!();
+    pub fn convert_to_layout<'a>(&self, id: usize, instance: &'a Instance) -> Layout<'a> {
+        let sheettype = instance.get_sheettype(self.sheettype_id);
+        Layout::from_blueprint(id, sheettype, &self.top_node, instance)
     }
 
     pub fn sheettype_id(&self) -> usize {
@@ -39,4 +45,9 @@ impl SendableLayout {
     pub fn usage(&self) -> f64 {
         self.usage
     }
-}
\ No newline at end of file
+
+    /// Layouts with equal keys cut the same pattern out of the same sheettype
+    pub fn pattern_key(&self) -> &Arc<PatternKey> {
+        self.pattern_key.get_or_init(|| Arc::new(PatternKey::new(self.sheettype_id, &self.top_node)))
+    }
+}
@@ -1,4 +1,5 @@
 use std::hash::{Hash, Hasher};
+use crate::core::entities::defect::Defect;
 use crate::core::orientation::Orientation;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -9,10 +10,36 @@ pub struct SheetType {
     value: u64,
     fixed_first_cut_orientation: Option<Orientation>,
     max_stages: u8,
+    kerf: u64,
+    trim: TrimMargins,
+    defects: Vec<Defect>,
+    remnant: bool,
+    stage_mode: StageMode,
+    min_waste_dimension: u64,
+    max_items: usize,
+    max_parttypes: usize,
+}
+
+/// Defines how the cuts of a pattern are counted towards the maximum number of stages.
+/// WithTrimming allows one additional cut beyond the last stage, which only separates a single part from waste (non-exact k-stage patterns).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StageMode {
+    #[default]
+    Exact,
+    WithTrimming,
+}
+
+/// Strips of material along the edges of a sheet which are cut off before it can be used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrimMargins {
+    pub left: u64,
+    pub right: u64,
+    pub top: u64,
+    pub bottom: u64,
 }
 
 impl SheetType {
-    pub fn new(id: usize, width: u64, height: u64, value: u64, fixed_first_cut_orientation: Option<Orientation>, max_stages: u8) -> SheetType {
+    pub fn new(id: usize, width: u64, height: u64, value: u64, fixed_first_cut_orientation: Option<Orientation>, max_stages: u8, kerf: u64) -> SheetType {
         SheetType {
             id,
             width,
@@ -20,9 +47,53 @@ impl SheetType {
             value,
             fixed_first_cut_orientation,
             max_stages,
+            kerf,
+            trim: TrimMargins::default(),
+            defects: vec![],
+            remnant: false,
+            stage_mode: StageMode::default(),
+            min_waste_dimension: 0,
+            max_items: usize::MAX,
+            max_parttypes: usize::MAX,
         }
     }
 
+    pub fn with_trim(mut self, trim: TrimMargins) -> Self {
+        self.trim = trim;
+        assert!(self.usable_width() > 0 && self.usable_height() > 0, "trim margins of sheettype {} leave no usable area", self.id);
+        self
+    }
+
+    pub fn with_defects(mut self, defects: Vec<Defect>) -> Self {
+        assert!(defects.iter().all(|d| d.width > 0 && d.height > 0), "defects of sheettype {} need a positive size", self.id);
+        self.defects = defects;
+        self
+    }
+
+    /// Marks the sheettype as a remnant (offcut) of an earlier job, of which only a single piece is in stock
+    pub fn with_remnant(mut self, remnant: bool) -> Self {
+        self.remnant = remnant;
+        self
+    }
+
+    pub fn with_stage_mode(mut self, stage_mode: StageMode) -> Self {
+        self.stage_mode = stage_mode;
+        self
+    }
+
+    /// The saw cannot produce waste pieces narrower than this
+    pub fn with_min_waste_dimension(mut self, min_waste_dimension: u64) -> Self {
+        self.min_waste_dimension = min_waste_dimension;
+        self
+    }
+
+    /// Limits the number of items and the number of distinct parttypes which can be produced from a single sheet
+    pub fn with_item_limits(mut self, max_items: usize, max_parttypes: usize) -> Self {
+        self.max_items = max_items;
+        self.max_parttypes = max_parttypes;
+        self
+    }
+
     pub fn id(&self) -> usize {
         self.id
     }
@@ -50,6 +121,75 @@ impl SheetType {
     pub fn max_stages(&self) -> u8 {
         self.max_stages
     }
+
+    pub fn stage_mode(&self) -> StageMode {
+        self.stage_mode
+    }
+
+    /// The deepest level a node can reach: one level per stage, plus one for the trimming cut if allowed.
+    /// Nodes beyond the maximum number of stages can only be trimmed waste.
+    pub fn max_node_level(&self) -> u8 {
+        match self.stage_mode {
+            StageMode::Exact => self.max_stages,
+            StageMode::WithTrimming => self.max_stages.saturating_add(1),
+        }
+    }
+
+    /// Width of the material removed by every cut of the saw
+    pub fn kerf(&self) -> u64 {
+        self.kerf
+    }
+
+    pub fn trim(&self) -> &TrimMargins {
+        &self.trim
+    }
+
+    pub fn defects(&self) -> &Vec<Defect> {
+        &self.defects
+    }
+
+    pub fn is_remnant(&self) -> bool {
+        self.remnant
+    }
+
+    /// The number and total length of the cuts which remove the trim strips.
+    /// The left and right strips are cut off first, the top and bottom strips span the remaining width.
+    pub fn trim_cuts(&self) -> (u64, u64) {
+        let n_vertical_cuts = (self.trim.left > 0) as u64 + (self.trim.right > 0) as u64;
+        let n_horizontal_cuts = (self.trim.top > 0) as u64 + (self.trim.bottom > 0) as u64;
+        (n_vertical_cuts + n_horizontal_cuts, n_vertical_cuts * self.height + n_horizontal_cuts * self.usable_width())
+    }
+
+    pub fn max_items(&self) -> usize {
+        self.max_items
+    }
+
+    pub fn max_parttypes(&self) -> usize {
+        self.max_parttypes
+    }
+
+    pub fn min_waste_dimension(&self) -> u64 {
+        self.min_waste_dimension
+    }
+
+    /// Whether an empty node of these dimensions (including its trailing kerf, see Node) leaves a piece of waste which is too thin to be cut.
+    /// Nodes which are lost entirely to the kerf do not leave a piece at all.
+    pub fn is_unmachinable_waste(&self, width: u64, height: u64) -> bool {
+        let (waste_width, waste_height) = (width.saturating_sub(self.kerf), height.saturating_sub(self.kerf));
+        waste_width > 0 && waste_height > 0 && u64::min(waste_width, waste_height) < self.min_waste_dimension
+    }
+
+    /// Width of the sheet which remains after trimming, every trim cut removes an additional kerf
+    pub fn usable_width(&self) -> u64 {
+        let n_trim_cuts = (self.trim.left > 0) as u64 + (self.trim.right > 0) as u64;
+        self.width.saturating_sub(self.trim.left + self.trim.right + n_trim_cuts * self.kerf)
+    }
+
+    /// Height of the sheet which remains after trimming, every trim cut removes an additional kerf
+    pub fn usable_height(&self) -> u64 {
+        let n_trim_cuts = (self.trim.top > 0) as u64 + (self.trim.bottom > 0) as u64;
+        self.height.saturating_sub(self.trim.top + self.trim.bottom + n_trim_cuts * self.kerf)
+    }
 }
 
 impl Hash for SheetType {
@@ -6,6 +6,7 @@ use itertools::Itertools;
 use crate::core::cost::Cost;
 use crate::core::entities::parttype::PartType;
 use crate::core::insertion::insertion_blueprint::InsertionBlueprint;
+use crate::core::insertion::node_blueprint::NodeBlueprint;
 use crate::core::layout_index::LayoutIndex;
 use crate::core::rotation::Rotation;
 use crate::optimization::problem::Problem;
@@ -35,25 +36,48 @@ impl<'a> InsertionOption<'a> {
 
     pub fn generate_blueprints(&self, problem: &Problem) -> Vec<InsertionBlueprint<'a>> {
         let layout = problem.get_layout(&self.layout_i);
+        if !layout.item_limits_allow(self.parttype) {
+            return vec![];
+        }
         let original_node = &layout.nodes()[self.original_node_i];
-        let max_stages = layout.sheettype().max_stages();
+        let sheettype = layout.sheettype();
         let node_blueprints = match self.rotation {
             Some(rotation) => {
-                original_node.generate_insertion_node_blueprints(self.parttype, rotation, max_stages, vec![])
+                original_node.generate_insertion_node_blueprints(self.parttype, rotation, sheettype, vec![])
             }
             None => {
-                let node_blueprints = original_node.generate_insertion_node_blueprints(self.parttype, Rotation::Default, max_stages, vec![]);
-                original_node.generate_insertion_node_blueprints(self.parttype, Rotation::Rotated, max_stages, node_blueprints)
+                let node_blueprints = original_node.generate_insertion_node_blueprints(self.parttype, Rotation::Default, sheettype, vec![]);
+                original_node.generate_insertion_node_blueprints(self.parttype, Rotation::Rotated, sheettype, node_blueprints)
             }
         };
-        let original_cost = original_node.calculate_cost();
+        let original_cost = original_node.calculate_cost(sheettype);
+        let parent_node = &layout.nodes()[original_node.parent().expect("original node has no parent")];
+
+        //Replacements under a fixed node keep their position on the sheet.
+        //Slivers which are lost entirely to the kerf are left out of the solution, shifting the pieces behind them, so they are not allowed there.
+        let parent_fixed = parent_node.is_fixed();
+        let kerf = sheettype.kerf();
+        let is_sliver = |nb: &NodeBlueprint| (nb.width() > 0 && nb.width() <= kerf) || (nb.height() > 0 && nb.height() <= kerf);
+
+        //Inserting the first part of an order into a layout spreads the order over one more layout
+        let order_spread_increase = match self.parttype.order() {
+            Some(order) if !layout.contains_order(order) => 1,
+            _ => 0,
+        };
 
         //Convert the node blueprints into insertion blueprints
-        node_blueprints.into_iter().map(|nbs| {
-            let new_cost = nbs.iter().map(|replacement| replacement.calculate_cost()).sum::<Cost>();
-            let insertion_cost = new_cost.subtract(&original_cost);
-            InsertionBlueprint::new(self.layout_i, self.original_node_i, nbs, self.parttype, insertion_cost)
-        }).collect_vec()
+        node_blueprints.into_iter()
+            .filter(|nbs| !parent_fixed || !nbs.iter().any(is_sliver))
+            .map(|nbs| {
+                let new_cost = nbs.iter().map(|replacement| replacement.calculate_cost(sheettype, original_node.level())).sum::<Cost>();
+                //Replacing the original node by multiple nodes requires additional cuts in the parent
+                let n_extra_cuts = nbs.iter().filter(|r| r.area() > 0).count().saturating_sub(1) as u64;
+                let insertion_cost = new_cost
+                    .add_cuts(n_extra_cuts, n_extra_cuts * parent_node.cut_length(kerf))
+                    .add_order_spread(order_spread_increase)
+                    .subtract(&original_cost);
+                InsertionBlueprint::new(self.layout_i, self.original_node_i, nbs, self.parttype, insertion_cost)
+            }).collect_vec()
     }
 
     pub fn parttype(&self) -> &'a PartType {
@@ -71,4 +95,50 @@ impl<'a> InsertionOption<'a> {
     pub fn layout_index(&self) -> &LayoutIndex {
         &self.layout_i
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::entities::sheettype::SheetType;
+    use crate::core::leftover_valuator;
+    use crate::core::orientation::Orientation;
+    use crate::optimization::instance::Instance;
+
+    /// Blueprints to insert the parttype into the largest empty node of the only layout (or a new one)
+    fn blueprints<'a>(problem: &Problem<'a>, parttype_id: usize) -> Vec<InsertionBlueprint<'a>> {
+        let layout_index = match problem.layouts().iter().next() {
+            Some((index, _)) => LayoutIndex::Existing(index),
+            None => LayoutIndex::Empty(0),
+        };
+        let node_index = problem.get_layout(&layout_index).sorted_empty_nodes()[0];
+        let parttype = problem.instance().get_parttype(parttype_id);
+        InsertionOption::new(layout_index, node_index, parttype, Some(Rotation::Default)).generate_blueprints(problem)
+    }
+
+    #[test]
+    fn item_limits() {
+        leftover_valuator::set_power(2.0);
+        let sheettype = SheetType::new(0, 10, 10, 100, Some(Orientation::Vertical), 1, 0).with_item_limits(3, 2);
+        let parttypes = (0..3).map(|id| (PartType::new(id, 1, 10, 10, Some(Rotation::Default), None), 10)).collect();
+        let instance = Instance::new(parttypes, vec![(sheettype, 10)]);
+        let mut problem = Problem::new(&instance, Some(0), 0);
+
+        problem.implement_insertion_blueprint(&blueprints(&problem, 0)[0]);
+        problem.implement_insertion_blueprint(&blueprints(&problem, 1)[0]);
+
+        //The layout contains the maximum number of parttypes, only parts of those can be added
+        assert!(blueprints(&problem, 2).is_empty());
+        problem.implement_insertion_blueprint(&blueprints(&problem, 0)[0]);
+
+        //The layout contains the maximum number of parts
+        assert!(blueprints(&problem, 0).is_empty());
+        assert!(blueprints(&problem, 2).is_empty());
+
+        //Removing the only part of a parttype makes room for both another part and another parttype
+        let (layout_index, layout) = problem.layouts().iter().next().unwrap();
+        let (node_index, _) = layout.nodes().iter().find(|(_, n)| n.parttype().is_some_and(|p| p.id() == 1)).unwrap();
+        problem.remove_node(node_index, LayoutIndex::Existing(layout_index));
+        assert!(!blueprints(&problem, 2).is_empty());
+    }
+}
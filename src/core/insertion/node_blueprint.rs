@@ -1,9 +1,12 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
 use generational_arena::{Arena, Index};
 use itertools::Itertools;
 
 use crate::core::cost::Cost;
 use crate::core::entities::node::Node;
 use crate::core::entities::parttype::PartType;
+use crate::core::entities::sheettype::SheetType;
 use crate::core::leftover_valuator;
 use crate::core::orientation::Orientation;
 
@@ -16,6 +19,7 @@ pub struct NodeBlueprint {
     children: Vec<NodeBlueprint>,
     parttype_id: Option<usize>,
     next_cut_orient: Orientation,
+    fixed: bool,
 }
 
 impl NodeBlueprint {
@@ -25,7 +29,11 @@ impl NodeBlueprint {
             Some(parttype) => Some(parttype.id()),
             None => None,
         };
-        Self { width, height, children, parttype_id, next_cut_orient }
+        Self { width, height, children, parttype_id, next_cut_orient, fixed: false }
+    }
+
+    pub fn new_fixed(width: u64, height: u64, next_cut_orient: Orientation) -> Self {
+        Self { fixed: true, ..Self::new(width, height, None, next_cut_orient) }
     }
 
     pub fn from_node(node_index: Index, nodes: &Arena<Node>) -> Self {
@@ -41,22 +49,57 @@ impl NodeBlueprint {
             .map(|child_index| NodeBlueprint::from_node(*child_index, nodes))
             .collect_vec();
 
-        Self { width, height, parttype_id, children, next_cut_orient }
+        Self { width, height, parttype_id, children, next_cut_orient, fixed: node.is_fixed() }
+    }
+
+    /// Marks the nodes which belong to the fixed structure around the defects of a sheet, e.g. after parsing a previously generated solution.
+    /// Fixed children are matched by their position, the free regions in between can contain any number of nodes.
+    /// Returns false if the node does not contain the structure.
+    pub fn apply_fixed_structure(&mut self, fixed_node: &NodeBlueprint) -> bool {
+        let matches = self.width == fixed_node.width && self.height == fixed_node.height && self.next_cut_orient == fixed_node.next_cut_orient;
+        if !matches {
+            return false;
+        }
+        self.fixed = true;
+
+        let offsets = |children: &Vec<NodeBlueprint>| children.iter()
+            .scan(0, |offset, c| {
+                let c_offset = *offset;
+                *offset += match self.next_cut_orient {
+                    Orientation::Horizontal => c.height,
+                    Orientation::Vertical => c.width,
+                };
+                Some(c_offset)
+            })
+            .collect_vec();
+        let (own_offsets, fixed_offsets) = (offsets(&self.children), offsets(&fixed_node.children));
+
+        fixed_node.children.iter().zip(fixed_offsets)
+            .filter(|(fixed_child, _)| fixed_child.fixed)
+            .all(|(fixed_child, fixed_offset)| {
+                match own_offsets.iter().position(|o| *o == fixed_offset) {
+                    Some(child_i) => self.children[child_i].apply_fixed_structure(fixed_child),
+                    None => false,
+                }
+            })
     }
 
     pub fn add_child(&mut self, child: NodeBlueprint) {
         self.children.push(child);
     }
 
-    pub fn calculate_cost(&self) -> Cost {
-        if self.parttype_id.is_some() {
-            return Cost::new(0, 0.0, 0, 0);
+    /// The level is the one the blueprint would occupy in a layout, nodes beyond the maximum number of stages are trimmed waste
+    pub fn calculate_cost(&self, sheettype: &SheetType, level: u8) -> Cost {
+        if self.parttype_id.is_some() || (self.fixed && self.children.is_empty()) || (self.children.is_empty() && level > sheettype.max_stages()) {
+            return Cost::empty(); //parts, fixed waste and trimmed waste
         } else if self.children.is_empty() {
-            return Cost::new(0, leftover_valuator::valuate(self.area()), 0, 0);
+            return Cost::empty().add_leftover_value(leftover_valuator::valuate(self.width.saturating_sub(sheettype.kerf()), self.height.saturating_sub(sheettype.kerf())));
         } else {
-            let mut cost = Cost::new(0, 0.0, 0, 0);
+            //The children are separated by one cut less than there are children
+            let n_cuts = self.children.iter().filter(|c| c.area() > 0).count().saturating_sub(1) as u64;
+            let mut cost = Cost::empty().add_cuts(n_cuts, n_cuts * self.cut_length(sheettype.kerf()));
             for child in &self.children {
-                cost = cost + child.calculate_cost();
+                cost = cost + child.calculate_cost(sheettype, level + 1);
             }
             return cost;
         }
@@ -78,14 +121,30 @@ impl NodeBlueprint {
         }
     }
 
+    /// Whether the blueprint contains an empty node which leaves a piece of waste that is too thin to be cut
+    pub fn contains_unmachinable_waste(&self, sheettype: &SheetType) -> bool {
+        match self.is_empty() {
+            true => sheettype.is_unmachinable_waste(self.width, self.height),
+            false => self.children.iter().any(|child| child.contains_unmachinable_waste(sheettype)),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.parttype_id.is_none() && self.children.is_empty()
+        self.parttype_id.is_none() && self.children.is_empty() && !self.fixed
     }
 
     pub fn area(&self) -> u64 {
         self.width * self.height
     }
 
+    /// The length of a single cut between the children of this node
+    pub fn cut_length(&self, kerf: u64) -> u64 {
+        match self.next_cut_orient {
+            Orientation::Horizontal => self.width.saturating_sub(kerf),
+            Orientation::Vertical => self.height.saturating_sub(kerf),
+        }
+    }
+
     pub fn width(&self) -> u64 {
         self.width
     }
@@ -106,4 +165,74 @@ impl NodeBlueprint {
         self.next_cut_orient
     }
 
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+
+}
+
+impl NodeBlueprint {
+    /// Two blueprints describe the same cutting pattern if their canonical forms are equal.
+    /// The order of the children of free nodes is irrelevant, as it is only decided when the pattern is written out.
+    /// Children without any area are not part of the pattern and are ignored.
+    fn canonical_form(&self) -> CanonicalNode {
+        let children = self.children.iter()
+            .filter(|c| c.area() > 0)
+            .map(|c| c.canonical_form());
+        let children = match self.fixed {
+            true => children.collect_vec(),
+            false => children.sorted().collect_vec(),
+        };
+        CanonicalNode {
+            width: self.width,
+            height: self.height,
+            horizontal: self.next_cut_orient == Orientation::Horizontal,
+            parttype_id: self.parttype_id,
+            fixed: self.fixed,
+            children,
+        }
+    }
+}
+
+/// Identifies the cutting pattern of a layout, layouts with equal keys cut identical patterns out of the same sheettype (see SendableLayout).
+/// The hash is computed once, equality is always confirmed on the full pattern.
+#[derive(Debug)]
+pub struct PatternKey {
+    sheettype_id: usize,
+    hash: u64,
+    pattern: CanonicalNode,
+}
+
+impl PatternKey {
+    pub fn new(sheettype_id: usize, top_node: &NodeBlueprint) -> Self {
+        let pattern = top_node.canonical_form();
+        let mut hasher = DefaultHasher::new();
+        sheettype_id.hash(&mut hasher);
+        pattern.hash(&mut hasher);
+        Self { sheettype_id, hash: hasher.finish(), pattern }
+    }
+}
+
+impl PartialEq for PatternKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.sheettype_id == other.sheettype_id && self.pattern == other.pattern
+    }
+}
+
+impl Eq for PatternKey {}
+
+impl Hash for PatternKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct CanonicalNode {
+    width: u64,
+    height: u64,
+    horizontal: bool,
+    parttype_id: Option<usize>,
+    fixed: bool,
+    children: Vec<CanonicalNode>,
 }
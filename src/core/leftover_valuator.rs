@@ -2,6 +2,7 @@ use std::cell::RefCell;
 
 thread_local! {
     static VALUATION_POWER : RefCell<Option<f32>> = RefCell::new(None);
+    static VALUED_REMNANT_DIMENSIONS : RefCell<Option<(u64, u64)>> = const { RefCell::new(None) };
 }
 
 pub fn set_power(power: f32) {
@@ -10,9 +11,28 @@ pub fn set_power(power: f32) {
     })
 }
 
-pub fn valuate(area: u64) -> f32 {
-    VALUATION_POWER.with(|p| {
-        let power = p.borrow().expect("valuation power not set for this thread!");
-        f32::powf(area as f32, power)
+/// If set, only leftovers which are usable remnants of at least these dimensions are valued
+pub fn set_valued_remnant_dimensions(min_dimensions: Option<(u64, u64)>) {
+    VALUED_REMNANT_DIMENSIONS.with(|d| {
+        *d.borrow_mut() = min_dimensions;
     })
-}
\ No newline at end of file
+}
+
+pub fn valuate(length: u64, height: u64) -> f32 {
+    let valued = VALUED_REMNANT_DIMENSIONS.with(|d| {
+        d.borrow().is_none_or(|min_dimensions| is_remnant(length, height, min_dimensions))
+    });
+    match valued {
+        false => 0.0,
+        true => VALUATION_POWER.with(|p| {
+            let power = p.borrow().expect("valuation power not set for this thread!");
+            f32::powf((length * height) as f32, power)
+        })
+    }
+}
+
+/// A leftover is a reusable remnant if it contains a rectangle of the minimum dimensions, in either orientation
+pub fn is_remnant(length: u64, height: u64, min_dimensions: (u64, u64)) -> bool {
+    let (min_length, min_height) = min_dimensions;
+    (length >= min_length && height >= min_height) || (length >= min_height && height >= min_length)
+}
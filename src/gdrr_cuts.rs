@@ -0,0 +1,26 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use gdrr_2bp::io::cut_instructions;
+use gdrr_2bp::io::json_format::JsonSolution;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let solution_file_path = PathBuf::from(args.get(1).expect("First cmd argument needs to be path to solution file"));
+
+    let solution_file = File::open(&solution_file_path).expect("solution file could not be opened");
+    let json_solution: JsonSolution = serde_json::from_reader(BufReader::new(&solution_file)).unwrap();
+
+    let operator_sheet = cut_instructions::generate_operator_sheet(&json_solution);
+
+    //Without an output path, the instructions are printed to the console
+    match args.get(2) {
+        Some(output_path) => {
+            std::fs::write(output_path, operator_sheet).expect("cut instructions could not be written");
+            println!("Cut instructions of {} cutting patterns written to {}", json_solution.cutting_patterns.len(), output_path);
+        }
+        None => print!("{}", operator_sheet),
+    }
+}
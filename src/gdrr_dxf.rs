@@ -0,0 +1,28 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use gdrr_2bp::io::dxf_export;
+use gdrr_2bp::io::json_format::JsonSolution;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let solution_file_path = PathBuf::from(args.get(1).expect("First cmd argument needs to be path to solution file"));
+    let dxf_path = PathBuf::from(args.get(2).expect("Second cmd argument needs to be path to DXF file or output directory"));
+
+    let solution_file = File::open(&solution_file_path).expect("solution file could not be opened");
+    let json_solution: JsonSolution = serde_json::from_reader(BufReader::new(&solution_file)).unwrap();
+
+    //A path with the dxf extension receives all cutting patterns, otherwise every cutting pattern is written to its own file
+    match dxf_path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dxf")) {
+        true => {
+            std::fs::write(&dxf_path, dxf_export::generate_solution(&json_solution)).expect("DXF file could not be written");
+            println!("{} cutting patterns written to {}", json_solution.cutting_patterns.len(), dxf_path.display());
+        }
+        false => {
+            let paths = dxf_export::write_cutting_patterns(&json_solution, &dxf_path);
+            println!("{} cutting patterns written to {}", paths.len(), dxf_path.display());
+        }
+    }
+}
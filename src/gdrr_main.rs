@@ -1,22 +1,20 @@
-use std::{env, thread};
+use std::env;
 use std::fs::File;
 use std::io::BufReader;
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::sync::mpsc::channel;
+use std::sync::{Arc, atomic};
+use std::sync::atomic::AtomicBool;
 use std::time::Instant;
 use once_cell::sync::Lazy;
 
 use gdrr_2bp::io::html_export::generate_solution;
-use gdrr_2bp::io::json_format::JsonInstance;
+use gdrr_2bp::io::json_format::JsonSolution;
 use gdrr_2bp::io::parser;
 use gdrr_2bp::optimization::config::Config;
-use gdrr_2bp::optimization::gdrr::GDRR;
-use gdrr_2bp::optimization::sol_collectors::global_sol_collector::GlobalSolCollector;
-use gdrr_2bp::optimization::sol_collectors::local_sol_collector::LocalSolCollector;
+use gdrr_2bp::optimization::solver::Solver;
 use gdrr_2bp::timed_println;
-use gdrr_2bp::COST_COMPARATOR;
+use gdrr_2bp::util::util;
 use mimalloc::MiMalloc;
 
 pub static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
@@ -42,60 +40,54 @@ fn main() {
             None
         }
     };
+    let initial_solution_path = match args.len() > 5 {
+        true => Some(PathBuf::from(args.get(5).unwrap())),
+        false => None
+    };
 
-    let input_file = File::open(&input_file_path).expect("input file could not be opened");
     let config_file = File::open(&config_file_path).expect("config file could not be opened");
-
-    let mut json_instance: JsonInstance = serde_json::from_reader(BufReader::new(&input_file)).unwrap();
     let config: Config = serde_json::from_reader(BufReader::new(&config_file)).unwrap();
 
+    let mut json_instance = parser::read_json_instance(&input_file_path, &config).unwrap_or_else(|errors| {
+        errors.iter().for_each(|error| eprintln!("{}", error));
+        panic!("input file could not be imported: {} errors found", errors.len());
+    });
+
     timed_println!("Config file loaded: {}", serde_json::to_string(&config).unwrap());
 
     let instance = parser::generate_instance(&mut json_instance, &config);
     timed_println!("Starting optimization of {} parts of {} different types for {} seconds", instance.total_part_qty(), instance.parts().len(), config.max_run_time.unwrap_or(usize::MAX));
     timed_println!("Press Ctrl+C to terminate manually");
 
-    let instance = Arc::new(instance);
-    let config = Arc::new(config);
-
-    let mut gdrr_thread_handlers = Vec::new();
-
-
-    let mut tx_syncs = Vec::new();
-    let (tx_solution_report, rx_solution_report) = channel();
-
-    for i in 0..config.n_threads {
-        let instance_thread = instance.clone();
-        let config_thread = config.clone();
-        let thread_name = format!("T{}", i);
-        let (tx_sync, rx_sync) = channel();
-        let tx_solution_report_thread = tx_solution_report.clone();
-        tx_syncs.push(tx_sync);
-
-
-        let handle = thread::Builder::new().name(thread_name).spawn(move || {
-            let local_sol_collector = LocalSolCollector::new(instance_thread.clone(), rx_sync, tx_solution_report_thread, COST_COMPARATOR);
-            let mut gdrr = GDRR::new(&instance_thread, &config_thread, local_sol_collector);
-            gdrr.lahc();
-        });
-        gdrr_thread_handlers.push(handle.expect("could not spawn thread"));
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || {
+        r.store(false, atomic::Ordering::SeqCst);
+    }).expect("Error setting Ctrl-C handler");
+
+    let mut solver = Solver::new(instance, config).with_running_flag(running);
+
+    if let Some(initial_solution_path) = initial_solution_path {
+        let initial_solution_file = File::open(&initial_solution_path).expect("initial solution file could not be opened");
+        let json_initial_solution: JsonSolution = serde_json::from_reader(BufReader::new(&initial_solution_file)).unwrap();
+        match parser::generate_solution_from_json(&json_initial_solution, solver.instance().clone(), solver.config()) {
+            Ok(initial_solution) => {
+                timed_println!("Initial solution loaded: {}", util::solution_stats_string(&initial_solution));
+                solver = solver.with_initial_solution(initial_solution);
+            }
+            Err(violation) => {
+                eprintln!("{}: {}", initial_solution_path.display(), violation);
+                timed_println!("Initial solution does not fit the instance, starting without it");
+            }
+        }
     }
 
-    let mut global_sol_collector = GlobalSolCollector::new(instance, config, tx_syncs, rx_solution_report, COST_COMPARATOR);
+    let config = solver.config().clone();
+    let result = solver.solve();
+    timed_println!("{} iterations in {:.2}s over {} threads (seed: {})", result.n_iterations(), result.run_time().as_secs_f64(), result.gdrr_stats().len(), result.seed());
 
-    global_sol_collector.monitor(gdrr_thread_handlers);
-
-    let json_solution = match (global_sol_collector.best_complete_solution().as_ref(), global_sol_collector.best_incomplete_solution().as_ref()) {
-        (Some(best_complete_solution), _) => {
-            Some(parser::generate_json_solution(&json_instance, best_complete_solution, &config_file_path))
-        }
-        (None, Some(best_incomplete_solution)) => {
-            Some(parser::generate_json_solution(&json_instance, best_incomplete_solution, &config_file_path))
-        }
-        (None, None) => {
-            None
-        }
-    };
+    let json_solution = result.best_solution()
+        .map(|best_solution| parser::generate_json_solution(&json_instance, best_solution, &config, &config_file_path, Some(result.seed())));
 
     if json_solution.is_some() {
         if let Some(json_solution_path) = json_solution_path {
@@ -0,0 +1,27 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use gdrr_2bp::io::json_format::JsonSolution;
+use gdrr_2bp::io::svg_export::{self, SvgOptions};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let solution_file_path = PathBuf::from(args.get(1).expect("First cmd argument needs to be path to solution file"));
+    let svg_directory_path = PathBuf::from(args.get(2).expect("Second cmd argument needs to be path to output directory"));
+    let flags = &args[usize::min(3, args.len())..];
+    for flag in flags {
+        assert!(flag == "--no-labels" || flag == "--no-dimensions", "unknown flag {}, expected --no-labels or --no-dimensions", flag);
+    }
+    let options = SvgOptions {
+        labels: !flags.iter().any(|f| f == "--no-labels"),
+        dimensions: !flags.iter().any(|f| f == "--no-dimensions"),
+    };
+
+    let solution_file = File::open(&solution_file_path).expect("solution file could not be opened");
+    let json_solution: JsonSolution = serde_json::from_reader(BufReader::new(&solution_file)).unwrap();
+
+    let paths = svg_export::write_cutting_patterns(&json_solution, &svg_directory_path, &options);
+    println!("{} cutting patterns written to {}", paths.len(), svg_directory_path.display());
+}
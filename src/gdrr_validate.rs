@@ -0,0 +1,55 @@
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use gdrr_2bp::io::json_format::JsonSolution;
+use gdrr_2bp::io::{parser, validate};
+use gdrr_2bp::optimization::config::Config;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    let input_file_path = PathBuf::from(args.get(1).expect("First cmd argument needs to be path to input file"));
+    let config_file_path = PathBuf::from(args.get(2).expect("Second cmd argument needs to be path to config file"));
+    let solution_file_path = PathBuf::from(args.get(3).expect("Third cmd argument needs to be path to solution file"));
+
+    let config_file = File::open(&config_file_path).expect("config file could not be opened");
+    let solution_file = File::open(&solution_file_path).expect("solution file could not be opened");
+
+    let config: Config = serde_json::from_reader(BufReader::new(&config_file)).unwrap();
+    //Solutions written before the X/Y coordinates and Placements were added are rejected here
+    let json_solution: JsonSolution = match serde_json::from_reader(BufReader::new(&solution_file)) {
+        Ok(json_solution) => json_solution,
+        Err(error) => {
+            println!("Solution file could not be parsed: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let json_instance = match parser::read_json_instance(&input_file_path, &config) {
+        Ok(json_instance) => json_instance,
+        Err(errors) => {
+            for error in errors.iter() {
+                println!("{}", error);
+            }
+            println!("Input file could not be imported: {} errors found", errors.len());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let violations = validate::validate(&json_instance, &json_solution, &config);
+
+    match violations.is_empty() {
+        true => {
+            println!("Solution is valid ({} cutting patterns)", json_solution.cutting_patterns.len());
+            ExitCode::SUCCESS
+        }
+        false => {
+            for violation in violations.iter() {
+                println!("{}", violation);
+            }
+            println!("Solution is invalid: {} violations found", violations.len());
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::io::json_format::{JsonInstance, JsonPartType, JsonRotation, JsonSheetType};
+
+/// Imports the items and objects of an instance from CSV files, e.g. cut lists exported by an ERP system.
+/// The columns are mapped onto the fields of the JsonInstance by the CsvImport section of the config.
+///
+/// All malformed rows are reported together, with the file and line number of every error,
+/// e.g. "cutlist.csv:12: demand 'two' is not a whole number".
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvImport {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default)]
+    pub header: CsvHeader,
+    /// All dimensions are multiplied by this factor and rounded, e.g. 10 to convert centimeters to millimeters
+    #[serde(default = "default_unit_scale")]
+    pub unit_scale: f64,
+    pub items: CsvItemColumns,
+    pub objects: CsvObjectColumns,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_unit_scale() -> f64 {
+    1.0
+}
+
+/// Whether the first line of a CSV file contains the column names.
+/// Auto assumes a header if any numeric column is referenced by name, or if none of them contains a number on the first line.
+/// A first line with only some invalid numbers is considered a malformed row instead.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvHeader {
+    #[default]
+    Auto,
+    Present,
+    Absent,
+}
+
+/// A column, referenced by its (zero-based) position or by its name in the header
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+/// Columns of the items CSV file, the optional ones can be left out of the mapping
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvItemColumns {
+    pub length: CsvColumn,
+    pub height: CsvColumn,
+    pub demand: CsvColumn,
+    /// Defaults to the area of the item
+    pub value: Option<CsvColumn>,
+    pub rotation: Option<CsvColumn>,
+    pub order: Option<CsvColumn>,
+}
+
+/// Columns of the objects CSV file, the optional ones can be left out of the mapping
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvObjectColumns {
+    /// Path of the objects CSV file, relative paths start from the directory of the items CSV file
+    pub path: PathBuf,
+    pub length: CsvColumn,
+    pub height: CsvColumn,
+    /// Defaults to an unlimited stock
+    pub stock: Option<CsvColumn>,
+    /// Defaults to the area of the object
+    pub cost: Option<CsvColumn>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+pub fn is_csv(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// Reads the items from the given CSV file and the objects from the CSV file referenced in the CsvImport
+pub fn read_json_instance(items_path: &Path, csv_import: &CsvImport) -> Result<JsonInstance, Vec<CsvError>> {
+    let objects_path = items_path.parent().unwrap_or(Path::new("")).join(&csv_import.objects.path);
+    let parttypes = read_file(items_path).and_then(|csv| parse_items(&csv, &items_path.display().to_string(), csv_import));
+    let sheettypes = read_file(&objects_path).and_then(|csv| parse_objects(&csv, &objects_path.display().to_string(), csv_import));
+
+    match (parttypes, sheettypes) {
+        (Ok(parttypes), Ok(sheettypes)) => Ok(JsonInstance {
+            name: items_path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().to_string()),
+            sheettypes,
+            parttypes,
+        }),
+        (parttypes, sheettypes) => Err(parttypes.err().into_iter().chain(sheettypes.err()).flatten().collect()),
+    }
+}
+
+/// A file which cannot be read is reported on line 0
+fn read_file(path: &Path) -> Result<String, Vec<CsvError>> {
+    std::fs::read_to_string(path).map_err(|err| vec![CsvError {
+        file: path.display().to_string(),
+        line: 0,
+        message: format!("file could not be read: {}", err),
+    }])
+}
+
+pub fn parse_items(csv: &str, file: &str, csv_import: &CsvImport) -> Result<Vec<JsonPartType>, Vec<CsvError>> {
+    let columns = &csv_import.items;
+    let numeric_columns = [Some(&columns.length), Some(&columns.height), Some(&columns.demand), columns.value.as_ref()];
+    let mut table = CsvTable::new(csv, file, csv_import, &numeric_columns.into_iter().flatten().collect::<Vec<_>>());
+    let length_i = table.resolve(&columns.length);
+    let height_i = table.resolve(&columns.height);
+    let demand_i = table.resolve(&columns.demand);
+    let value_i = columns.value.as_ref().map(|c| table.resolve(c));
+    let rotation_i = columns.rotation.as_ref().map(|c| table.resolve(c));
+    let order_i = columns.order.as_ref().map(|c| table.resolve(c));
+
+    let mut parttypes = vec![];
+    for record in std::mem::take(&mut table.records) {
+        let length = table.dimension(&record, "length", length_i);
+        let height = table.dimension(&record, "height", height_i);
+        let demand = table.integer(&record, "demand", demand_i);
+        let value = value_i.and_then(|i| table.optional_integer(&record, "value", i));
+        let rotation = rotation_i.and_then(|i| table.rotation(&record, i));
+        let order = order_i.flatten().and_then(|i| table.text(&record, i));
+
+        if let (Some(length), Some(height), Some(demand)) = (length, height, demand) {
+            parttypes.push(JsonPartType {
+                length,
+                height,
+                demand: demand as usize,
+                value: value.unwrap_or(length * height),
+                rotation,
+                order,
+                reference: None,
+            });
+        }
+    }
+    table.finish(parttypes)
+}
+
+pub fn parse_objects(csv: &str, file: &str, csv_import: &CsvImport) -> Result<Vec<JsonSheetType>, Vec<CsvError>> {
+    let columns = &csv_import.objects;
+    let numeric_columns = [Some(&columns.length), Some(&columns.height), columns.stock.as_ref(), columns.cost.as_ref()];
+    let mut table = CsvTable::new(csv, file, csv_import, &numeric_columns.into_iter().flatten().collect::<Vec<_>>());
+    let length_i = table.resolve(&columns.length);
+    let height_i = table.resolve(&columns.height);
+    let stock_i = columns.stock.as_ref().map(|c| table.resolve(c));
+    let cost_i = columns.cost.as_ref().map(|c| table.resolve(c));
+
+    let mut sheettypes = vec![];
+    for record in std::mem::take(&mut table.records) {
+        let length = table.dimension(&record, "length", length_i);
+        let height = table.dimension(&record, "height", height_i);
+        let stock = stock_i.and_then(|i| table.optional_integer(&record, "stock", i));
+        let cost = cost_i.and_then(|i| table.optional_integer(&record, "cost", i));
+
+        if let (Some(length), Some(height)) = (length, height) {
+            sheettypes.push(JsonSheetType {
+                length,
+                height,
+                stock: stock.map(|s| s as usize),
+                cost: cost.unwrap_or(length * height),
+                kerf: None,
+                first_cut: None,
+                max_stages: None,
+                stage_mode: None,
+                max_items: None,
+                max_part_types: None,
+                trim_left: None,
+                trim_right: None,
+                trim_top: None,
+                trim_bottom: None,
+                defects: None,
+                remnant: None,
+                reference: None,
+            });
+        }
+    }
+    table.finish(sheettypes)
+}
+
+/// A non-empty record of a CSV file, split into fields, with the line on which it starts
+struct CsvRecord {
+    line: usize,
+    fields: Vec<String>,
+}
+
+/// The records of a CSV file and the errors found while reading them
+struct CsvTable<'a> {
+    file: &'a str,
+    csv_import: &'a CsvImport,
+    header: Option<Vec<String>>,
+    records: Vec<CsvRecord>,
+    errors: Vec<CsvError>,
+}
+
+impl<'a> CsvTable<'a> {
+    /// The `numeric_columns` are used to detect the header in CsvHeader::Auto mode
+    fn new(csv: &str, file: &'a str, csv_import: &'a CsvImport, numeric_columns: &[&CsvColumn]) -> Self {
+        let (lines, unclosed_quote_line) = split_lines(csv.trim_start_matches('\u{feff}'));
+        let mut records = lines.into_iter()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(line_nr, line)| CsvRecord { line: line_nr, fields: split_record(line, csv_import.delimiter) })
+            .collect::<Vec<_>>();
+
+        let is_number = |record: &CsvRecord, i: usize| record.fields.get(i).is_some_and(|field| parse_number(field, csv_import.delimiter).is_some());
+        let has_header = match csv_import.header {
+            CsvHeader::Present => true,
+            CsvHeader::Absent => false,
+            CsvHeader::Auto => numeric_columns.iter().any(|column| matches!(column, CsvColumn::Name(_)))
+                || records.first().is_some_and(|record| numeric_columns.iter().all(|column| match column {
+                    CsvColumn::Index(i) => !is_number(record, *i),
+                    CsvColumn::Name(_) => true,
+                })),
+        };
+        let header = match has_header && !records.is_empty() {
+            true => Some(records.remove(0).fields),
+            false => None,
+        };
+
+        let mut table = Self { file, csv_import, header, records, errors: vec![] };
+        if let Some(line) = unclosed_quote_line {
+            table.error(line, "quoted field is not closed before the end of the file".to_string());
+        }
+        table
+    }
+
+    /// Finds the position of a column, errors are reported on the first line
+    fn resolve(&mut self, column: &CsvColumn) -> Option<usize> {
+        match (column, self.header.as_ref()) {
+            (CsvColumn::Index(i), _) => Some(*i),
+            (CsvColumn::Name(name), Some(header)) => {
+                let index = header.iter().position(|field| field.trim().eq_ignore_ascii_case(name.trim()));
+                if index.is_none() {
+                    self.error(1, format!("column '{}' not found in the header", name));
+                }
+                index
+            }
+            (CsvColumn::Name(name), None) => {
+                self.error(1, format!("column '{}' is referenced by name, but the file has no header", name));
+                None
+            }
+        }
+    }
+
+    /// The trimmed content of a field, None if the field is empty or missing
+    fn text(&self, record: &CsvRecord, column: usize) -> Option<String> {
+        record.fields.get(column)
+            .map(|field| field.trim())
+            .filter(|field| !field.is_empty())
+            .map(|field| field.to_string())
+    }
+
+    /// A positive dimension, scaled to the units of the solver
+    fn dimension(&mut self, record: &CsvRecord, name: &str, column: Option<usize>) -> Option<u64> {
+        let field = self.text(record, column?);
+        let scaled = field.as_deref()
+            .and_then(|field| parse_number(field, self.csv_import.delimiter))
+            .map(|number| (number * self.csv_import.unit_scale).round())
+            .filter(|scaled| *scaled >= 1.0 && *scaled < u64::MAX as f64);
+        if scaled.is_none() {
+            self.error(record.line, format!("{} '{}' is not a positive number", name, field.unwrap_or_default()));
+        }
+        scaled.map(|scaled| scaled as u64)
+    }
+
+    fn integer(&mut self, record: &CsvRecord, name: &str, column: Option<usize>) -> Option<u64> {
+        let field = self.text(record, column?);
+        let integer = field.as_deref().and_then(|field| field.parse::<u64>().ok());
+        if integer.is_none() {
+            self.error(record.line, format!("{} '{}' is not a whole number", name, field.unwrap_or_default()));
+        }
+        integer
+    }
+
+    /// An integer which is allowed to be empty
+    fn optional_integer(&mut self, record: &CsvRecord, name: &str, column: Option<usize>) -> Option<u64> {
+        match column.and_then(|c| self.text(record, c)) {
+            Some(_) => self.integer(record, name, column),
+            None => None,
+        }
+    }
+
+    /// Rotation is allowed for "allowed", "yes", "true" or "1" and fixed for "fixed", "no", "false" or "0"
+    fn rotation(&mut self, record: &CsvRecord, column: Option<usize>) -> Option<JsonRotation> {
+        let field = self.text(record, column?)?;
+        match field.to_lowercase().as_str() {
+            "allowed" | "yes" | "true" | "1" => Some(JsonRotation::Allowed),
+            "fixed" | "no" | "false" | "0" => Some(JsonRotation::Fixed),
+            _ => {
+                self.error(record.line, format!("rotation '{}' is neither allowed nor fixed", field));
+                None
+            }
+        }
+    }
+
+    fn error(&mut self, line: usize, message: String) {
+        self.errors.push(CsvError { file: self.file.to_string(), line, message });
+    }
+
+    fn finish<T>(mut self, result: Vec<T>) -> Result<Vec<T>, Vec<CsvError>> {
+        if result.is_empty() && self.errors.is_empty() {
+            self.error(1, "no rows found".to_string());
+        }
+        match self.errors.is_empty() {
+            true => Ok(result),
+            false => Err(self.errors),
+        }
+    }
+}
+
+/// Splits a CSV file into records at the line breaks outside of quoted fields, so quoted fields can span multiple lines.
+/// Every record is returned with the line on which it starts. A record with a quoted field which is not closed is left out,
+/// the line on which it starts is returned separately.
+fn split_lines(csv: &str) -> (Vec<(usize, &str)>, Option<usize>) {
+    let mut records = vec![];
+    let (mut start, mut start_line, mut line, mut in_quotes) = (0, 1, 1, false);
+    for (i, c) in csv.char_indices() {
+        match c {
+            //Doubled quotes within a quoted field toggle twice
+            '"' => in_quotes = !in_quotes,
+            '\n' => {
+                line += 1;
+                if !in_quotes {
+                    records.push((start_line, csv[start..i].trim_end_matches('\r')));
+                    (start, start_line) = (i + 1, line);
+                }
+            }
+            _ => {}
+        }
+    }
+    match in_quotes {
+        true => (records, Some(start_line)),
+        false => {
+            records.push((start_line, &csv[start..]));
+            (records, None)
+        }
+    }
+}
+
+/// Splits a record into its fields. Fields can be enclosed in double quotes to contain the delimiter, quotes within them are doubled.
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => in_quotes = !in_quotes,
+            (c, false) if c == delimiter => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parses a decimal number. Unless the comma is the delimiter, it is accepted as decimal separator as well.
+fn parse_number(field: &str, delimiter: char) -> Option<f64> {
+    let field = field.trim();
+    let number = match delimiter {
+        ',' => field.parse::<f64>(),
+        _ => field.replace(',', ".").parse::<f64>(),
+    };
+    number.ok().filter(|n| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_import(config: &str) -> CsvImport {
+        serde_json::from_str(config).unwrap()
+    }
+
+    fn index_import() -> CsvImport {
+        csv_import(r#"{"items": {"length": 0, "height": 1, "demand": 2, "order": 3}, "objects": {"path": "objects.csv", "length": 0, "height": 1}}"#)
+    }
+
+    fn error_messages(errors: Vec<CsvError>) -> Vec<String> {
+        errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(split_record(r#"1,"a, ""quoted"" b",2"#, ','), vec!["1", r#"a, "quoted" b"#, "2"]);
+
+        let parttypes = parse_items("300,200,1,\"order 1, line 2\"\n", "items.csv", &index_import()).unwrap();
+        assert_eq!(parttypes[0].order.as_deref(), Some("order 1, line 2"));
+    }
+
+    #[test]
+    fn quoted_line_breaks() {
+        let parttypes = parse_items("300,200,1,\"order 1\r\nline 2\"\r\n400,100,2,3\r\n", "items.csv", &index_import()).unwrap();
+        assert_eq!(parttypes.len(), 2);
+        assert_eq!(parttypes[0].order.as_deref(), Some("order 1\r\nline 2"));
+
+        //Errors are reported on the line on which the record starts
+        let errors = parse_items("300,200,1,\"order 1\nline 2\"\n400,x,2,3\n", "items.csv", &index_import()).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:3: height 'x' is not a positive number"]);
+
+        let errors = parse_items("300,200,1,3\n400,100,2,\"order 2\n", "items.csv", &index_import()).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:2: quoted field is not closed before the end of the file"]);
+    }
+
+    #[test]
+    fn semicolon_delimiter_with_decimal_commas() {
+        let import = csv_import(r#"{"delimiter": ";", "items": {"length": 0, "height": 1, "demand": 2}, "objects": {"path": "objects.csv", "length": 0, "height": 1}}"#);
+        let parttypes = parse_items("60,5;40;3\n12.5;7,0;1\n", "items.csv", &import).unwrap();
+        assert_eq!((parttypes[0].length, parttypes[0].height, parttypes[0].demand), (61, 40, 3));
+        assert_eq!((parttypes[1].length, parttypes[1].height, parttypes[1].demand), (13, 7, 1));
+
+        let errors = parse_items("60,5;40;3,5\n", "items.csv", &import).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: demand '3,5' is not a whole number"]);
+    }
+
+    #[test]
+    fn unit_scale() {
+        let import = csv_import(r#"{"unitScale": 10, "items": {"length": 0, "height": 1, "demand": 2, "value": 3}, "objects": {"path": "objects.csv", "length": 0, "height": 1, "cost": 2}}"#);
+        let parttypes = parse_items("30.25,20,2,7\n30,20,1,\n", "items.csv", &import).unwrap();
+        assert_eq!((parttypes[0].length, parttypes[0].height, parttypes[0].value), (303, 200, 7));
+        assert_eq!(parttypes[1].value, 300 * 200);
+
+        let sheettypes = parse_objects("244,122,5\n", "objects.csv", &import).unwrap();
+        assert_eq!((sheettypes[0].length, sheettypes[0].height, sheettypes[0].cost), (2440, 1220, 5));
+
+        let errors = parse_items("0.01,20,2\n", "items.csv", &import).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: length '0.01' is not a positive number"]);
+    }
+
+    #[test]
+    fn named_columns() {
+        let import = csv_import(r#"{"items": {"length": "Length", "height": "Width", "demand": "Qty"}, "objects": {"path": "objects.csv", "length": "L", "height": "H", "stock": "Stock"}}"#);
+        let parttypes = parse_items("Qty,Width,Length\n2,200,300\n", "items.csv", &import).unwrap();
+        assert_eq!((parttypes[0].length, parttypes[0].height, parttypes[0].demand), (300, 200, 2));
+
+        let sheettypes = parse_objects("L,H,Stock\n2440,1220,\n2000,1000,3\n", "objects.csv", &import).unwrap();
+        assert_eq!(sheettypes[0].stock, None);
+        assert_eq!(sheettypes[1].stock, Some(3));
+    }
+
+    #[test]
+    fn missing_columns() {
+        let import = csv_import(r#"{"items": {"length": "Length", "height": "Width", "demand": "Qty"}, "objects": {"path": "objects.csv", "length": 0, "height": 1}}"#);
+        let errors = parse_items("Length,Width\n300,200\n", "items.csv", &import).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: column 'Qty' not found in the header"]);
+
+        let import = csv_import(r#"{"header": "absent", "items": {"length": 0, "height": 1, "demand": "Qty"}, "objects": {"path": "objects.csv", "length": 0, "height": 1}}"#);
+        let errors = parse_items("300,200,1\n", "items.csv", &import).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: column 'Qty' is referenced by name, but the file has no header"]);
+
+        let errors = parse_items("300,200,1\n300,200\n", "items.csv", &index_import()).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:2: demand '' is not a whole number"]);
+    }
+
+    #[test]
+    fn header_auto_detection() {
+        let parttypes = parse_items("Length,Height,Demand\n300,200,1\n\n30,20,2\n", "items.csv", &index_import()).unwrap();
+        assert_eq!(parttypes.len(), 2);
+
+        //A first row with only some malformed numbers is not a header
+        let errors = parse_items("1O0,50,2\n30,20,1\n", "items.csv", &index_import()).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: length '1O0' is not a positive number"]);
+
+        let errors = parse_items("Length,Height,Demand\n", "items.csv", &index_import()).err().unwrap();
+        assert_eq!(error_messages(errors), vec!["items.csv:1: no rows found"]);
+    }
+
+    #[test]
+    fn unreadable_files() {
+        let errors = read_json_instance(Path::new("/nonexistent/items.csv"), &index_import()).err().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().starts_with("/nonexistent/items.csv:0: file could not be read: "));
+        assert!(errors[1].to_string().starts_with("/nonexistent/objects.csv:0: file could not be read: "));
+    }
+}
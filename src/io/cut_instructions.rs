@@ -0,0 +1,210 @@
+use std::fmt::Write;
+
+use crate::io::json_format::{JsonCPNode, JsonCPNodeType, JsonCut, JsonCutPiece, JsonOrientation, JsonSolution};
+
+/// Generates the ordered list of cuts of a cutting pattern from its tree, with the coordinates relative to the top left corner of the object.
+/// The cuts of a node are made before the cuts of its children, so the trim cuts come first, then the stage 1 cuts,
+/// then the stage 2 cuts of the first strip followed by the cuts of its pieces, before moving on to the next strip.
+pub fn generate_cuts(root: &JsonCPNode) -> Vec<JsonCut> {
+    let mut cuts = vec![];
+    collect_cuts(root, (None, 0), &mut cuts);
+    cuts
+}
+
+/// `stage` contains the orientation and number of the last stage of cuts that was applied to reach this node (see validate).
+fn collect_cuts(node: &JsonCPNode, stage: (Option<JsonOrientation>, u8), cuts: &mut Vec<JsonCut>) {
+    let orientation = match &node.orientation {
+        Some(orientation) => orientation.clone(),
+        None => return,
+    };
+    //Cuts only start a new stage if they are perpendicular to the previous stage, the trim strips are cut off before the first stage
+    let has_trim = node.children.iter().any(|c| matches!(c.node_type, JsonCPNodeType::Trim));
+    let child_stage = match (node.children.len(), &stage.0) {
+        _ if has_trim => stage.clone(),
+        (1, _) if node.children[0].length == node.length && node.children[0].height == node.height => stage.clone(),
+        (_, Some(prev_orientation)) if *prev_orientation == orientation => stage.clone(),
+        (_, _) => (Some(orientation.clone()), stage.1 + 1),
+    };
+    let cut_stage = match has_trim {
+        true => 0,
+        false => child_stage.1,
+    };
+    let node_end = match orientation {
+        JsonOrientation::H => node.y + node.height,
+        JsonOrientation::V => node.x + node.length,
+    };
+
+    for (i, child) in node.children.iter().enumerate() {
+        let (cut_position, ((start_x, start_y), (end_x, end_y))) = match orientation {
+            JsonOrientation::H => {
+                let cut_y = child.y + child.height;
+                (cut_y, ((node.x, cut_y), (node.x + node.length, cut_y)))
+            }
+            JsonOrientation::V => {
+                let cut_x = child.x + child.length;
+                (cut_x, ((cut_x, node.y), (cut_x, node.y + node.height)))
+            }
+        };
+
+        //The last child only requires a cut if some material remains behind it, which is lost to the kerf
+        let remaining_piece = match node.children.get(i + 1) {
+            None if cut_position >= node_end => break,
+            None => None,
+            Some(next) if i + 2 == node.children.len() => Some(cut_piece(next)),
+            Some(next) => {
+                let (length, height) = match orientation {
+                    JsonOrientation::H => (node.length, node_end - next.y),
+                    JsonOrientation::V => (node_end - next.x, node.height),
+                };
+                Some(JsonCutPiece { length, height, node_type: JsonCPNodeType::Structure, item: None })
+            }
+        };
+
+        cuts.push(JsonCut {
+            stage: cut_stage,
+            orientation: orientation.clone(),
+            start_x,
+            start_y,
+            end_x,
+            end_y,
+            pieces: [Some(cut_piece(child)), remaining_piece].into_iter().flatten().collect(),
+        });
+    }
+
+    for child in node.children.iter() {
+        collect_cuts(child, child_stage.clone(), cuts);
+    }
+}
+
+fn cut_piece(node: &JsonCPNode) -> JsonCutPiece {
+    JsonCutPiece {
+        length: node.length,
+        height: node.height,
+        node_type: node.node_type.clone(),
+        item: node.item,
+    }
+}
+
+/// Generates a plain text overview of the cuts of every cutting pattern, to be followed by the operator of the saw
+pub fn generate_operator_sheet(json_solution: &JsonSolution) -> String {
+    let mut sheet = String::new();
+    writeln!(sheet, "Cut instructions for {}", json_solution.name).unwrap();
+    writeln!(sheet, "Coordinates are relative to the top left corner of the object, the kerf is removed below or right of every cut line.").unwrap();
+
+    for (i, json_cp) in json_solution.cutting_patterns.iter().enumerate() {
+        //Solutions without cuts are converted on the fly
+        let generated_cuts;
+        let cuts = match json_cp.cuts.is_empty() {
+            true => {
+                generated_cuts = generate_cuts(&json_cp.root);
+                &generated_cuts
+            }
+            false => &json_cp.cuts,
+        };
+        writeln!(sheet).unwrap();
+        writeln!(sheet, "Cutting pattern {}: {}x object {} [{}x{}], {} cuts",
+                 i, json_cp.multiplicity, json_cp.object, json_cp.root.length, json_cp.root.height, cuts.len()).unwrap();
+        for (j, cut) in cuts.iter().enumerate() {
+            let orientation = match cut.orientation {
+                JsonOrientation::H => "horizontal",
+                JsonOrientation::V => "vertical",
+            };
+            let stage = match cut.stage {
+                0 => "trim".to_string(),
+                stage => format!("stage {}", stage),
+            };
+            let pieces = cut.pieces.iter().map(describe_piece).collect::<Vec<_>>().join(" | ");
+            writeln!(sheet, "  {:>3}. {:<8} {:<10} ({}, {}) -> ({}, {}): {}",
+                     j + 1, stage, orientation, cut.start_x, cut.start_y, cut.end_x, cut.end_y, pieces).unwrap();
+        }
+    }
+    sheet
+}
+
+fn describe_piece(piece: &JsonCutPiece) -> String {
+    let kind = match (&piece.node_type, piece.item) {
+        (JsonCPNodeType::Item, Some(item)) => format!("item {}", item),
+        (JsonCPNodeType::Item, None) => "item".to_string(),
+        (JsonCPNodeType::Structure, _) => "piece".to_string(),
+        (JsonCPNodeType::Leftover, _) => "leftover".to_string(),
+        (JsonCPNodeType::Trim, _) => "trim".to_string(),
+        (JsonCPNodeType::Defect, _) => "defect".to_string(),
+    };
+    format!("{} [{}x{}]", kind, piece.length, piece.height)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn node(length: u64, height: u64, x: u64, y: u64, node_type: &str) -> Value {
+        json!({"Length": length, "Height": height, "X": x, "Y": y, "Type": node_type, "Children": []})
+    }
+
+    fn item(length: u64, height: u64, x: u64, y: u64, item: usize) -> Value {
+        let mut item_node = node(length, height, x, y, "Item");
+        item_node["Item"] = json!(item);
+        item_node
+    }
+
+    fn structure(length: u64, height: u64, x: u64, y: u64, orientation: &str, children: Vec<Value>) -> Value {
+        json!({"Length": length, "Height": height, "X": x, "Y": y, "Orientation": orientation, "Type": "Structure", "Children": children})
+    }
+
+    /// A 3-stage pattern on a [12x12] object with a kerf of 1 and a left trim margin of 1, without a list of cuts
+    fn solution() -> JsonSolution {
+        let root = structure(12, 12, 0, 0, "V", vec![
+            node(1, 12, 0, 0, "Trim"),
+            structure(10, 12, 2, 0, "H", vec![
+                structure(10, 5, 2, 0, "V", vec![
+                    item(4, 5, 2, 0, 0),
+                    structure(5, 5, 7, 0, "H", vec![item(5, 2, 7, 0, 1), node(5, 2, 7, 3, "Leftover")]),
+                ]),
+                structure(10, 6, 2, 6, "V", vec![item(3, 6, 2, 6, 2), node(6, 6, 6, 6, "Leftover")]),
+            ]),
+        ]);
+        serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": [{"Length": 12, "Height": 12, "Stock": null, "Cost": 144, "Kerf": 1, "TrimLeft": 1}],
+            "Items": [
+                {"Length": 4, "Height": 5, "Demand": 1, "Value": 20},
+                {"Length": 5, "Height": 2, "Demand": 1, "Value": 10},
+                {"Length": 3, "Height": 6, "Demand": 1, "Value": 18}
+            ],
+            "CuttingPatterns": [{"Object": 0, "Multiplicity": 2, "Usage": 0.33, "Root": root, "Placements": []}],
+            "Statistics": {"UsagePct": 33.0, "PartAreaIncludedPct": 100.0, "NObjectsUsed": 2, "MaterialCost": 288, "RunTimeMs": 0, "ConfigPath": ""}
+        })).unwrap()
+    }
+
+    #[test]
+    fn cut_order() {
+        let json_solution = solution();
+        let stages_and_lines = generate_cuts(&json_solution.cutting_patterns[0].root).iter()
+            .map(|cut| (cut.stage, (cut.start_x, cut.start_y), (cut.end_x, cut.end_y)))
+            .collect::<Vec<_>>();
+        //The trim cut comes first, every strip is cut completely before moving on to the next one
+        assert_eq!(stages_and_lines, vec![
+            (0, (1, 0), (1, 12)),
+            (1, (2, 5), (12, 5)),
+            (2, (6, 0), (6, 5)),
+            (3, (7, 2), (12, 2)),
+            (2, (5, 6), (5, 12)),
+        ]);
+    }
+
+    #[test]
+    fn operator_sheet_without_cuts() {
+        let operator_sheet = generate_operator_sheet(&solution());
+        assert_eq!(operator_sheet.lines().skip(2).collect::<Vec<_>>(), vec![
+            "",
+            "Cutting pattern 0: 2x object 0 [12x12], 5 cuts",
+            "    1. trim     vertical   (1, 0) -> (1, 12): trim [1x12] | piece [10x12]",
+            "    2. stage 1  horizontal (2, 5) -> (12, 5): piece [10x5] | piece [10x6]",
+            "    3. stage 2  vertical   (6, 0) -> (6, 5): item 0 [4x5] | piece [5x5]",
+            "    4. stage 3  horizontal (7, 2) -> (12, 2): item 1 [5x2] | leftover [5x2]",
+            "    5. stage 2  vertical   (5, 6) -> (5, 12): item 2 [3x6] | leftover [6x6]",
+        ]);
+    }
+}
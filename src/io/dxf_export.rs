@@ -0,0 +1,194 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::io::cut_instructions;
+use crate::io::json_format::{JsonCP, JsonCPNode, JsonCPNodeType, JsonOrientation, JsonSheetType, JsonSolution};
+
+//Exports cutting patterns as ASCII DXF (R12) drawings, which can be imported by most CNC saws and routers.
+//The outline of the sheet, the items, the leftovers and the guillotine cuts are drawn on separate layers.
+//Cuts are drawn along the center of the kerf. Unlike the JSON, the y axis of a DXF drawing points upwards,
+//so the top left corner of a sheet is located at (0, height).
+
+/// Layers of the drawing with their colour index
+const LAYERS: [(&str, u8); 4] = [(SHEET_LAYER, 7), (PARTS_LAYER, 3), (LEFTOVERS_LAYER, 1), (CUTS_LAYER, 5)];
+const SHEET_LAYER: &str = "SHEET";
+const PARTS_LAYER: &str = "PARTS";
+const LEFTOVERS_LAYER: &str = "LEFTOVERS";
+const CUTS_LAYER: &str = "CUTS";
+
+/// Fraction of the largest sheet dimension which separates consecutive sheets in a single drawing
+const SHEET_SPACING: f64 = 0.1;
+
+/// Generates a drawing of a single cutting pattern, with the bottom left corner of the sheet at the origin
+pub fn generate_cutting_pattern(json_cp: &JsonCP, json_sheet: &JsonSheetType) -> String {
+    let mut writer = DxfWriter::new();
+    draw_cutting_pattern(&mut writer, json_cp, json_sheet, 0.0);
+    writer.finish()
+}
+
+/// Generates a drawing of all cutting patterns of the solution, placed next to each other from left to right
+pub fn generate_solution(json_solution: &JsonSolution) -> String {
+    let spacing = json_solution.cutting_patterns.iter()
+        .map(|json_cp| u64::max(json_cp.root.length, json_cp.root.height))
+        .max().unwrap_or(0) as f64 * SHEET_SPACING;
+
+    let mut writer = DxfWriter::new();
+    let mut offset = 0.0;
+    for json_cp in json_solution.cutting_patterns.iter() {
+        draw_cutting_pattern(&mut writer, json_cp, &json_solution.sheettypes[json_cp.object], offset);
+        offset += json_cp.root.length as f64 + spacing;
+    }
+    writer.finish()
+}
+
+/// Writes every cutting pattern of the solution to its own DXF file in the given directory, named after its index.
+/// Returns the paths of the written files.
+pub fn write_cutting_patterns(json_solution: &JsonSolution, directory: &Path) -> Vec<PathBuf> {
+    fs::create_dir_all(directory).expect("DXF directory could not be created");
+    json_solution.cutting_patterns.iter().enumerate()
+        .map(|(i, json_cp)| {
+            let path = directory.join(format!("pattern_{}.dxf", i));
+            let dxf = generate_cutting_pattern(json_cp, &json_solution.sheettypes[json_cp.object]);
+            fs::write(&path, dxf).expect("DXF file could not be written");
+            path
+        })
+        .collect()
+}
+
+fn draw_cutting_pattern(writer: &mut DxfWriter, json_cp: &JsonCP, json_sheet: &JsonSheetType, x_offset: f64) {
+    let root = &json_cp.root;
+    let sheet_height = root.height as f64;
+    let kerf = json_sheet.kerf.unwrap_or(0);
+
+    //Converts a position on the sheet (top left origin, y downwards) to the drawing
+    let to_drawing = |(x, y): (f64, f64)| (x_offset + x, sheet_height - y);
+
+    writer.rectangle(SHEET_LAYER, to_drawing((0.0, 0.0)), to_drawing((root.length as f64, sheet_height)));
+    draw_node(writer, root, &to_drawing);
+
+    //The cuts are drawn from the same instructions as the operator sheet, solutions without them are converted on the fly
+    let generated_cuts;
+    let cuts = match json_cp.cuts.is_empty() {
+        true => {
+            generated_cuts = cut_instructions::generate_cuts(root);
+            &generated_cuts
+        }
+        false => &json_cp.cuts,
+    };
+    let half_kerf = kerf as f64 / 2.0;
+    for cut in cuts {
+        //The cut line runs along the far edge of the first piece, the kerf is removed behind it
+        let (start, end) = match cut.orientation {
+            JsonOrientation::H => ((cut.start_x as f64, cut.start_y as f64 + half_kerf), (cut.end_x as f64, cut.end_y as f64 + half_kerf)),
+            JsonOrientation::V => ((cut.start_x as f64 + half_kerf, cut.start_y as f64), (cut.end_x as f64 + half_kerf, cut.end_y as f64)),
+        };
+        writer.line(CUTS_LAYER, to_drawing(start), to_drawing(end));
+    }
+}
+
+fn draw_node(writer: &mut DxfWriter, node: &JsonCPNode, to_drawing: &impl Fn((f64, f64)) -> (f64, f64)) {
+    let (x, y) = (node.x as f64, node.y as f64);
+    let (length, height) = (node.length as f64, node.height as f64);
+    match node.node_type {
+        JsonCPNodeType::Item => writer.rectangle(PARTS_LAYER, to_drawing((x, y)), to_drawing((x + length, y + height))),
+        JsonCPNodeType::Leftover => writer.rectangle(LEFTOVERS_LAYER, to_drawing((x, y)), to_drawing((x + length, y + height))),
+        JsonCPNodeType::Structure | JsonCPNodeType::Trim | JsonCPNodeType::Defect => {}
+    }
+
+    for child in node.children.iter() {
+        draw_node(writer, child, to_drawing);
+    }
+}
+
+/// Writes the group code/value pairs of an ASCII DXF file
+struct DxfWriter {
+    entities: String,
+}
+
+impl DxfWriter {
+    fn new() -> Self {
+        Self { entities: String::new() }
+    }
+
+    fn pair(buffer: &mut String, code: u16, value: impl Display) {
+        buffer.push_str(&format!("{}\n{}\n", code, value));
+    }
+
+    fn line(&mut self, layer: &str, from: (f64, f64), to: (f64, f64)) {
+        let buffer = &mut self.entities;
+        DxfWriter::pair(buffer, 0, "LINE");
+        DxfWriter::pair(buffer, 8, layer);
+        DxfWriter::pair(buffer, 10, from.0);
+        DxfWriter::pair(buffer, 20, from.1);
+        DxfWriter::pair(buffer, 30, 0.0);
+        DxfWriter::pair(buffer, 11, to.0);
+        DxfWriter::pair(buffer, 21, to.1);
+        DxfWriter::pair(buffer, 31, 0.0);
+    }
+
+    /// A closed polyline between two opposite corners
+    fn rectangle(&mut self, layer: &str, corner: (f64, f64), opposite_corner: (f64, f64)) {
+        let buffer = &mut self.entities;
+        DxfWriter::pair(buffer, 0, "POLYLINE");
+        DxfWriter::pair(buffer, 8, layer);
+        DxfWriter::pair(buffer, 66, 1);
+        DxfWriter::pair(buffer, 10, 0.0);
+        DxfWriter::pair(buffer, 20, 0.0);
+        DxfWriter::pair(buffer, 30, 0.0);
+        DxfWriter::pair(buffer, 70, 1);
+        let vertices = [corner, (opposite_corner.0, corner.1), opposite_corner, (corner.0, opposite_corner.1)];
+        for (vx, vy) in vertices {
+            DxfWriter::pair(buffer, 0, "VERTEX");
+            DxfWriter::pair(buffer, 8, layer);
+            DxfWriter::pair(buffer, 10, vx);
+            DxfWriter::pair(buffer, 20, vy);
+            DxfWriter::pair(buffer, 30, 0.0);
+        }
+        DxfWriter::pair(buffer, 0, "SEQEND");
+        DxfWriter::pair(buffer, 8, layer);
+    }
+
+    /// Wraps the entities in a complete drawing, including the header and the layer definitions
+    fn finish(self) -> String {
+        let mut dxf = String::new();
+        DxfWriter::pair(&mut dxf, 0, "SECTION");
+        DxfWriter::pair(&mut dxf, 2, "HEADER");
+        DxfWriter::pair(&mut dxf, 9, "$ACADVER");
+        DxfWriter::pair(&mut dxf, 1, "AC1009");
+        DxfWriter::pair(&mut dxf, 0, "ENDSEC");
+
+        DxfWriter::pair(&mut dxf, 0, "SECTION");
+        DxfWriter::pair(&mut dxf, 2, "TABLES");
+        DxfWriter::pair(&mut dxf, 0, "TABLE");
+        DxfWriter::pair(&mut dxf, 2, "LTYPE");
+        DxfWriter::pair(&mut dxf, 70, 1);
+        DxfWriter::pair(&mut dxf, 0, "LTYPE");
+        DxfWriter::pair(&mut dxf, 2, "CONTINUOUS");
+        DxfWriter::pair(&mut dxf, 70, 0);
+        DxfWriter::pair(&mut dxf, 3, "Solid line");
+        DxfWriter::pair(&mut dxf, 72, 65);
+        DxfWriter::pair(&mut dxf, 73, 0);
+        DxfWriter::pair(&mut dxf, 40, 0.0);
+        DxfWriter::pair(&mut dxf, 0, "ENDTAB");
+        DxfWriter::pair(&mut dxf, 0, "TABLE");
+        DxfWriter::pair(&mut dxf, 2, "LAYER");
+        DxfWriter::pair(&mut dxf, 70, LAYERS.len());
+        for (name, color) in LAYERS {
+            DxfWriter::pair(&mut dxf, 0, "LAYER");
+            DxfWriter::pair(&mut dxf, 2, name);
+            DxfWriter::pair(&mut dxf, 70, 0);
+            DxfWriter::pair(&mut dxf, 62, color);
+            DxfWriter::pair(&mut dxf, 6, "CONTINUOUS");
+        }
+        DxfWriter::pair(&mut dxf, 0, "ENDTAB");
+        DxfWriter::pair(&mut dxf, 0, "ENDSEC");
+
+        DxfWriter::pair(&mut dxf, 0, "SECTION");
+        DxfWriter::pair(&mut dxf, 2, "ENTITIES");
+        dxf.push_str(&self.entities);
+        DxfWriter::pair(&mut dxf, 0, "ENDSEC");
+        DxfWriter::pair(&mut dxf, 0, "EOF");
+        dxf
+    }
+}
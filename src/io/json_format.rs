@@ -20,6 +20,10 @@ pub struct JsonSolution {
     pub parttypes: Vec<JsonPartType>,
     #[serde(rename = "CuttingPatterns")]
     pub cutting_patterns: Vec<JsonCP>,
+    #[serde(rename = "Remnants", default, skip_serializing_if = "Vec::is_empty")]
+    pub remnants: Vec<JsonRemnant>,
+    #[serde(rename = "ConsumedRemnants", default, skip_serializing_if = "Vec::is_empty")]
+    pub consumed_remnants: Vec<usize>,
     #[serde(rename = "Statistics")]
     pub statistics: JsonSolutionStats,
 
@@ -33,9 +37,43 @@ pub struct JsonSheetType {
     pub stock: Option<usize>,
     pub cost: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub kerf: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_cut: Option<JsonOrientation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_stages: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage_mode: Option<JsonStageMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_part_types: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_left: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_right: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_top: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_bottom: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defects: Option<Vec<JsonDefect>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remnant: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reference: Option<usize>,
 }
 
+/// Region of an object which cannot be used, relative to its top left corner
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonDefect {
+    pub x: u64,
+    pub y: u64,
+    pub length: u64,
+    pub height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct JsonPartType {
@@ -44,6 +82,11 @@ pub struct JsonPartType {
     pub demand: usize,
     pub value: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<JsonRotation>,
+    /// The customer order this item belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reference: Option<usize>,
 }
 
@@ -51,8 +94,24 @@ pub struct JsonPartType {
 #[serde(rename_all = "PascalCase")]
 pub struct JsonCP {
     pub object: usize,
+    /// The number of times this pattern is cut, from as many identical objects
+    #[serde(default = "default_multiplicity")]
+    pub multiplicity: usize,
     pub usage: f64,
+    #[serde(default)]
+    pub n_cuts: u64,
+    #[serde(default)]
+    pub cut_length: u64,
     pub root: JsonCPNode,
+    /// The cuts of the pattern, in the order in which they are made
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cuts: Vec<JsonCut>,
+    /// All items of the pattern with their position, so the tree does not need to be walked
+    pub placements: Vec<JsonPlacement>,
+}
+
+fn default_multiplicity() -> usize {
+    1
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -60,6 +119,9 @@ pub struct JsonCP {
 pub struct JsonCPNode {
     pub length: u64,
     pub height: u64,
+    /// Coordinates of the top left corner of the node, relative to the top left corner of the object
+    pub x: u64,
+    pub y: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orientation: Option<JsonOrientation>,
     #[serde(rename = "Type")]
@@ -69,19 +131,94 @@ pub struct JsonCPNode {
     pub children: Vec<JsonCPNode>,
 }
 
+/// An item of a cutting pattern, rotated if its length and height are swapped with respect to the input
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonPlacement {
+    pub item: usize,
+    pub x: u64,
+    pub y: u64,
+    pub length: u64,
+    pub height: u64,
+    pub rotated: bool,
+}
+
+/// A single guillotine cut through a piece of the object.
+/// The cut line runs along the far edge of the first piece (its bottom or right side), the kerf is removed behind it.
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
+pub struct JsonCut {
+    /// Trim cuts are part of stage 0
+    pub stage: u8,
+    pub orientation: JsonOrientation,
+    pub start_x: u64,
+    pub start_y: u64,
+    pub end_x: u64,
+    pub end_y: u64,
+    /// The piece which is separated by the cut, followed by the remaining piece
+    pub pieces: Vec<JsonCutPiece>,
+}
+
+/// A piece of material resulting from a cut, pieces of type Structure are cut further
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonCutPiece {
+    pub length: u64,
+    pub height: u64,
+    #[serde(rename = "Type")]
+    pub node_type: JsonCPNodeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
 pub enum JsonOrientation {
     H,
     V,
 }
 
+/// Whether a final trimming cut beyond the maximum number of stages is allowed, to separate an item from waste
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum JsonStageMode {
+    Exact,
+    WithTrimming,
+}
+
+/// Whether an item can be rotated, e.g. items with a grain direction are Fixed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum JsonRotation {
+    Allowed,
+    Fixed,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub enum JsonCPNodeType {
     Structure,
     Item,
     Leftover,
+    Trim,
+    Defect,
+}
+
+/// A leftover which is large enough to be reused, located on the object of one of the cutting patterns.
+/// The coordinates are relative to the top left corner of the object.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JsonRemnant {
+    pub cutting_pattern: usize,
+    pub object: usize,
+    /// Equal to the multiplicity of the cutting pattern
+    pub quantity: usize,
+    pub x: u64,
+    pub y: u64,
+    pub length: u64,
+    pub height: u64,
+    /// The remnant as an entry for the Objects of a later instance
+    pub as_object: JsonSheetType,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -91,7 +228,16 @@ pub struct JsonSolutionStats {
     pub part_area_included_pct: f32,
     pub n_objects_used: usize,
     pub material_cost: u64,
+    #[serde(default)]
+    pub n_cuts: u64,
+    #[serde(default)]
+    pub cut_length: u64,
+    /// The number of (order, object) pairs: every order counts once for each object containing its items
+    #[serde(default)]
+    pub order_spread: u64,
     pub run_time_ms: usize,
     pub config_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
 }
 
@@ -1,3 +1,8 @@
 pub mod parser;
 pub mod json_format;
 pub mod html_export;
+pub mod validate;
+pub mod csv_import;
+pub mod svg_export;
+pub mod dxf_export;
+pub mod cut_instructions;
\ No newline at end of file
@@ -1,29 +1,70 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use itertools::Itertools;
+use crate::core::entities::defect::{self, Defect};
 use crate::core::entities::parttype::PartType;
 
 use crate::core::entities::sendable_layout::SendableLayout;
-use crate::core::entities::sheettype::SheetType;
-use crate::core::insertion::node_blueprint::NodeBlueprint;
+use crate::core::entities::sheettype::{SheetType, StageMode, TrimMargins};
+use crate::core::insertion::node_blueprint::{NodeBlueprint, PatternKey};
+use crate::core::leftover_valuator;
 use crate::core::orientation::Orientation;
 use crate::core::rotation::Rotation;
-use crate::io::json_format::{JsonCP, JsonCPNode, JsonCPNodeType, JsonInstance, JsonOrientation, JsonSolution, JsonSolutionStats};
+use crate::io::csv_import::{self, CsvError};
+use crate::io::cut_instructions;
+use crate::io::json_format::{JsonCP, JsonCPNode, JsonCPNodeType, JsonInstance, JsonOrientation, JsonPlacement, JsonRemnant, JsonRotation, JsonSheetType, JsonSolution, JsonSolutionStats, JsonStageMode};
+use crate::io::validate::Violation;
 use crate::optimization::config::{Config, SheetValuationMode};
 use crate::optimization::instance::Instance;
+use crate::optimization::problem::Problem;
 use crate::optimization::solutions::sendable_solution::SendableSolution;
 use crate::optimization::solutions::solution::Solution;
+use crate::timed_println;
+
+/// In the RemnantsFirst sheet valuation mode, remnants are valued at this fraction of their area
+const REMNANT_VALUE_DIVISOR: u64 = 100;
+
+/// Reads the input file, which is either a JsonInstance or a CSV file of items (see io::csv_import).
+/// CSV files require a csvImport section in the config.
+pub fn read_json_instance(input_path: &Path, config: &Config) -> Result<JsonInstance, Vec<CsvError>> {
+    match csv_import::is_csv(input_path) {
+        true => {
+            let csv_import = config.csv_import.as_ref().expect("CSV input requires a csvImport section in the config");
+            csv_import::read_json_instance(input_path, csv_import)
+        }
+        false => {
+            let input_file = File::open(input_path).expect("input file could not be opened");
+            Ok(serde_json::from_reader(BufReader::new(&input_file)).unwrap())
+        }
+    }
+}
 
 pub fn generate_instance(json_instance: &mut JsonInstance, config: &Config) -> Instance {
     let mut part_id = 0;
     let mut parts = Vec::new();
+    //Orders are identified by their index of first occurrence
+    let mut orders: HashMap<&str, usize> = HashMap::new();
     for json_part in json_instance.parttypes.iter_mut() {
         json_part.reference = Some(part_id);
+        let rotation_allowed = match json_part.rotation {
+            Some(rotation) => rotation == JsonRotation::Allowed,
+            None => config.rotation_allowed,
+        };
+        let order = json_part.order.as_deref().map(|order| {
+            let n_orders = orders.len();
+            *orders.entry(order).or_insert(n_orders)
+        });
         let parttype = PartType::new(
             part_id,
             json_part.length,
             json_part.height,
-            if config.rotation_allowed { None } else { Some(Rotation::Default) },
+            json_part.value,
+            if rotation_allowed { None } else { Some(Rotation::Default) },
+            order,
         );
         let demand = json_part.demand;
         parts.push((parttype, demand));
@@ -34,23 +75,65 @@ pub fn generate_instance(json_instance: &mut JsonInstance, config: &Config) -> I
     let mut sheets = Vec::new();
     for json_sheet in json_instance.sheettypes.iter_mut() {
         json_sheet.reference = Some(sheet_id);
-        let sheet_value = match config.sheet_valuation_mode{
-            SheetValuationMode::Area => json_sheet.length * json_sheet.height,
-            SheetValuationMode::Cost => json_sheet.cost
+        let remnant = json_sheet.remnant.unwrap_or(false);
+        assert!(!remnant || json_sheet.stock.is_some(), "remnant object {} needs a stock, every physical piece has to be accounted for", sheet_id);
+
+        //Remnants are nearly free, but still valued, so unused remnants are not added to a solution
+        let sheet_value = match (&config.sheet_valuation_mode, remnant) {
+            (SheetValuationMode::Area, _) | (SheetValuationMode::RemnantsFirst, false) => json_sheet.length * json_sheet.height,
+            (SheetValuationMode::Cost, _) => json_sheet.cost,
+            (SheetValuationMode::RemnantsFirst, true) => u64::max(json_sheet.length * json_sheet.height / REMNANT_VALUE_DIVISOR, 1),
+        };
+
+        //The first cut orientation, maximum number of stages and item limits defined on the sheet override the ones in the config
+        let first_cut_orientation = match json_sheet.first_cut {
+            Some(JsonOrientation::H) => Some(Orientation::Horizontal),
+            Some(JsonOrientation::V) => Some(Orientation::Vertical),
+            None => None,
+        };
+        let max_stages = json_sheet.max_stages.or(config.max_stages).unwrap_or(u8::MAX);
+        let stage_mode = match json_sheet.stage_mode.or(config.stage_mode) {
+            Some(JsonStageMode::WithTrimming) => StageMode::WithTrimming,
+            Some(JsonStageMode::Exact) | None => StageMode::Exact,
         };
 
-        let max_stages = config.max_stages.unwrap_or(u8::MAX);
+        //A kerf defined on the sheet overrides the one in the config, the effective kerf is written back for the output
+        json_sheet.kerf = json_sheet.kerf.or(config.kerf);
+        let kerf = json_sheet.kerf.unwrap_or(0);
 
         let sheettype = SheetType::new(
             sheet_id,
             json_sheet.length,
             json_sheet.height,
             sheet_value,
-            None,
-            max_stages
+            first_cut_orientation,
+            max_stages,
+            kerf
+        ).with_trim(TrimMargins {
+            left: json_sheet.trim_left.unwrap_or(0),
+            right: json_sheet.trim_right.unwrap_or(0),
+            top: json_sheet.trim_top.unwrap_or(0),
+            bottom: json_sheet.trim_bottom.unwrap_or(0),
+        }).with_defects(json_sheet.defects.iter().flatten().map(|d| Defect {
+            x: d.x,
+            y: d.y,
+            width: d.length,
+            height: d.height,
+        }).collect())
+        .with_remnant(remnant)
+        .with_stage_mode(stage_mode)
+        .with_min_waste_dimension(config.min_waste_dimension.unwrap_or(0))
+        .with_item_limits(
+            json_sheet.max_items.or(config.max_items).unwrap_or(usize::MAX),
+            json_sheet.max_part_types.or(config.max_part_types).unwrap_or(usize::MAX),
         );
 
-        let stock = json_sheet.stock.unwrap_or(usize::MAX);
+        let mut stock = json_sheet.stock.unwrap_or(usize::MAX);
+        if defect::covers_usable_area(&sheettype) {
+            //The sheettype is kept without stock, so the ids of the other objects do not change
+            timed_println!("Object {} is entirely covered by defects and will not be used", sheet_id);
+            stock = 0;
+        }
         sheets.push((sheettype, stock));
         sheet_id += 1;
     }
@@ -58,23 +141,104 @@ pub fn generate_instance(json_instance: &mut JsonInstance, config: &Config) -> I
     Instance::new(parts, sheets)
 }
 
-pub fn generate_json_solution(json_instance: &JsonInstance, solution: &SendableSolution, config_path: &PathBuf) -> JsonSolution {
+/// Parses a previously generated JsonSolution back into a SendableSolution of the given instance.
+/// Leftover valuation happens on the calling thread, so it is configured here as well.
+/// A solution which does not fit the instance is rejected with the path of the first offending node.
+pub fn generate_solution_from_json(json_solution: &JsonSolution, instance: Arc<Instance>, config: &Config) -> Result<SendableSolution, Violation> {
+    leftover_valuator::set_power(config.leftover_valuation_power);
+    leftover_valuator::set_valued_remnant_dimensions(config.valued_remnant_dimensions());
+
+    let mut problem = Problem::new(instance.as_ref(), None, 0);
+    for (i, json_cp) in json_solution.cutting_patterns.iter().enumerate() {
+        let path = format!("CuttingPatterns[{}]", i);
+        if json_cp.object >= instance.sheets().len() {
+            return Err(Violation::new(&path, format!("object {} does not exist", json_cp.object)));
+        }
+        let sheettype = instance.get_sheettype(json_cp.object);
+        if defect::covers_usable_area(sheettype) {
+            return Err(Violation::new(&path, format!("object {} is entirely covered by defects", json_cp.object)));
+        }
+
+        let (usable_node, usable_path) = strip_trim(&json_cp.root, format!("{}.Root", path))?;
+        let (usable_width, usable_height) = (sheettype.usable_width(), sheettype.usable_height());
+        if (usable_node.length, usable_node.height) != (usable_width, usable_height) {
+            return Err(Violation::new(&usable_path, format!("usable area [{}x{}] does not match object {} [{}x{}]",
+                usable_node.length, usable_node.height, json_cp.object, usable_width, usable_height)));
+        }
+        if usable_node.children.is_empty() {
+            return Err(Violation::new(&usable_path, "usable area is not cut".to_string()));
+        }
+
+        let mut top_node = convert_json_cp_node_to_node_bp(usable_node, None, sheettype.kerf(), &instance, &usable_path)?;
+        if let Some(first_cut) = sheettype.fixed_first_cut_orientation() && first_cut != top_node.next_cut_orient() {
+            return Err(Violation::new(&usable_path, format!("first cut is {:?}, object {} requires {:?}", top_node.next_cut_orient(), json_cp.object, first_cut)));
+        }
+        if !sheettype.defects().is_empty() && !top_node.apply_fixed_structure(&defect::generate_defect_structure(sheettype, top_node.next_cut_orient())) {
+            return Err(Violation::new(&usable_path, format!("cutting pattern does not match the structure around the defects of object {}", json_cp.object)));
+        }
+        for _ in 0..json_cp.multiplicity {
+            problem.restore_layout_from_blueprint(json_cp.object, &top_node).map_err(|message| Violation::new(&path, message))?;
+        }
+    }
+    let problem_solution = problem.create_solution(&None, None);
+
+    Ok(SendableSolution::new(instance.clone(), &problem_solution))
+}
+
+pub fn generate_json_solution(json_instance: &JsonInstance, solution: &SendableSolution, config: &Config, config_path: &PathBuf, seed: Option<u64>) -> JsonSolution {
     let name = json_instance.name.clone();
     let sheettypes = json_instance.sheettypes.clone();
     let parttypes = json_instance.parttypes.clone();
 
-    let cutting_patterns = solution.layouts().iter()
+    //Identical layouts are collapsed into a single cutting pattern
+    let mut multiplicities: HashMap<&PatternKey, usize> = HashMap::new();
+    let unique_layouts = solution.layouts().iter()
         .sorted_by(|a, b| { a.usage().partial_cmp(&b.usage()).unwrap().reverse() })
-        .map(|l| { convert_layout_to_json_cp(l) }
-        ).collect::<Vec<JsonCP>>();
+        .filter(|l| {
+            let multiplicity = multiplicities.entry(l.pattern_key().as_ref()).or_insert(0);
+            *multiplicity += 1;
+            *multiplicity == 1
+        })
+        .collect_vec();
+
+    let cutting_patterns = unique_layouts.into_iter()
+        .map(|l| {
+            let mut json_cp = convert_layout_to_json_cp(l, solution.instance());
+            json_cp.multiplicity = multiplicities[l.pattern_key().as_ref()];
+            json_cp
+        }).collect::<Vec<JsonCP>>();
+
+    let remnants = match config.min_remnant_dimensions() {
+        Some(min_dimensions) => cutting_patterns.iter().enumerate()
+            .flat_map(|(i, json_cp)| {
+                let mut remnants = vec![];
+                collect_remnants(&json_cp.root, min_dimensions, &mut |node| {
+                    let as_object = remnant_as_object(&sheettypes[json_cp.object], node.length, node.height, json_cp.multiplicity);
+                    remnants.push(JsonRemnant { cutting_pattern: i, object: json_cp.object, quantity: json_cp.multiplicity, x: node.x, y: node.y, length: node.length, height: node.height, as_object });
+                });
+                remnants
+            })
+            .collect_vec(),
+        None => vec![],
+    };
+
+    let consumed_remnants = solution.layouts().iter()
+        .map(|l| l.sheettype_id())
+        .filter(|id| solution.instance().get_sheettype(*id).is_remnant())
+        .sorted()
+        .collect_vec();
 
     let statistics = JsonSolutionStats {
         usage_pct: (solution.usage() * 100.0) as f32,
         part_area_included_pct: (solution.cost().part_area_fraction_included() * 100.0) as f32,
         n_objects_used: solution.n_layouts(),
         material_cost: solution.cost().material_cost,
+        n_cuts: solution.cost().n_cuts,
+        cut_length: solution.cost().cut_length,
+        order_spread: solution.cost().order_spread,
         run_time_ms: crate::EPOCH.elapsed().as_millis() as usize,
         config_path: config_path.to_str().unwrap().to_string(),
+        seed,
     };
 
     JsonSolution {
@@ -82,31 +246,115 @@ pub fn generate_json_solution(json_instance: &JsonInstance, solution: &SendableS
         sheettypes,
         parttypes,
         cutting_patterns,
+        remnants,
+        consumed_remnants,
         statistics,
     }
 }
 
-pub fn convert_layout_to_json_cp(layout: &SendableLayout) -> JsonCP {
+/// Walks the JsonCPNode tree and reports every leftover that qualifies as a reusable remnant
+fn collect_remnants(node: &JsonCPNode, min_dimensions: (u64, u64), report: &mut impl FnMut(&JsonCPNode)) {
+    if matches!(node.node_type, JsonCPNodeType::Leftover) && leftover_valuator::is_remnant(node.length, node.height, min_dimensions) {
+        report(node);
+    }
+    for child in node.children.iter() {
+        collect_remnants(child, min_dimensions, report);
+    }
+}
+
+/// Converts a remnant into an object which can be added to the stock of a later instance.
+/// Its cost is the share of the object's cost corresponding to its area, the kerf and cutting restrictions of the object are kept.
+pub fn remnant_as_object(object: &JsonSheetType, length: u64, height: u64, quantity: usize) -> JsonSheetType {
+    let cost = (object.cost as u128 * (length * height) as u128 / (object.length * object.height) as u128) as u64;
+    JsonSheetType {
+        length,
+        height,
+        stock: Some(quantity),
+        cost,
+        kerf: object.kerf,
+        first_cut: object.first_cut.clone(),
+        max_stages: object.max_stages,
+        stage_mode: object.stage_mode,
+        max_items: object.max_items,
+        max_part_types: object.max_part_types,
+        trim_left: None,
+        trim_right: None,
+        trim_top: None,
+        trim_bottom: None,
+        defects: None,
+        remnant: Some(true),
+        reference: None,
+    }
+}
+
+/// Walks the JsonCPNode tree and lists every item with its position
+fn collect_placements(node: &JsonCPNode, instance: &Instance, placements: &mut Vec<JsonPlacement>) {
+    if let (JsonCPNodeType::Item, Some(item)) = (&node.node_type, node.item) {
+        let parttype = instance.get_parttype(item);
+        let rotated = (node.length, node.height) != (parttype.width(), parttype.height());
+        placements.push(JsonPlacement { item, x: node.x, y: node.y, length: node.length, height: node.height, rotated });
+    }
+    for child in node.children.iter() {
+        collect_placements(child, instance, placements);
+    }
+}
+
+/// Internally, all nodes include the kerf of the cut that separates them from their next sibling (see Node).
+/// In the JsonCP the kerf is removed again, so all dimensions correspond to the actual pieces of material.
+/// The layout only covers the usable area of the sheet, the trim strips around it are added to the JsonCP.
+pub fn convert_layout_to_json_cp(layout: &SendableLayout, instance: &Instance) -> JsonCP {
     let object = layout.sheettype_id();
-    let root = convert_node_bp_to_json_cp_node(layout.top_node());
+    let sheettype = instance.get_sheettype(object);
+    //The usable area starts behind the trim strips on the left and top of the sheet
+    let trim = sheettype.trim();
+    let usable_position = (
+        if trim.left > 0 { trim.left + sheettype.kerf() } else { 0 },
+        if trim.top > 0 { trim.top + sheettype.kerf() } else { 0 },
+    );
+    let root = add_trim(convert_node_bp_to_json_cp_node(layout.top_node(), usable_position, sheettype.kerf()), sheettype);
+    let mut placements = vec![];
+    collect_placements(&root, instance, &mut placements);
+    let cuts = cut_instructions::generate_cuts(&root);
+    debug_assert!(cuts.len() as u64 == layout.cost().n_cuts, "cut instructions do not match the number of cuts of the layout");
     let usage = layout.usage();
 
     JsonCP {
         object,
-        root,
+        multiplicity: 1,
         usage,
+        n_cuts: layout.cost().n_cuts,
+        cut_length: layout.cost().cut_length,
+        root,
+        cuts,
+        placements,
     }
 }
 
-pub fn convert_node_bp_to_json_cp_node(node: &NodeBlueprint) -> JsonCPNode {
+/// `position` contains the coordinates of the top left corner of the node on the object.
+pub fn convert_node_bp_to_json_cp_node(node: &NodeBlueprint, position: (u64, u64), kerf: u64) -> JsonCPNode {
+    //The order of the fixed structure around defects needs to be preserved, all other children are sorted by usage
+    let children = match node.is_fixed() {
+        true => node.children().iter().collect_vec(),
+        false => node.children().iter().sorted_by(|a, b| a.calculate_usage().partial_cmp(&b.calculate_usage()).unwrap().reverse()).collect_vec(),
+    };
     let mut json_children = Vec::new();
-    for child in node.children().iter().sorted_by(|a, b| a.calculate_usage().partial_cmp(&b.calculate_usage()).unwrap().reverse()) {
-        json_children.push(convert_node_bp_to_json_cp_node(child));
+    let mut child_position = position;
+    for child in children {
+        //Nodes which are not wider than the kerf are turned into sawdust
+        if child.width() > kerf && child.height() > kerf {
+            json_children.push(convert_node_bp_to_json_cp_node(child, child_position, kerf));
+            //Internally, the children include the kerf behind them
+            match node.next_cut_orient() {
+                Orientation::Horizontal => child_position.1 += child.height(),
+                Orientation::Vertical => child_position.0 += child.width(),
+            }
+        }
     }
-    let length = node.width();
-    let height = node.height();
+    let length = node.width() - kerf;
+    let height = node.height() - kerf;
 
     let node_type = match (node.parttype_id(), node.children().is_empty()) {
+        (None, true) if node.is_fixed() => JsonCPNodeType::Defect,
         (None, true) => JsonCPNodeType::Leftover,
         (None, false) => JsonCPNodeType::Structure,
         (Some(_), true) => JsonCPNodeType::Item,
@@ -129,9 +377,146 @@ pub fn convert_node_bp_to_json_cp_node(node: &NodeBlueprint) -> JsonCPNode {
     JsonCPNode {
         length,
         height,
+        x: position.0,
+        y: position.1,
         orientation,
         node_type,
         item,
         children: json_children,
     }
+}
+
+/// Wraps the usable area of a sheet in the trim strips along its edges.
+/// The top and bottom strips are the innermost, the left and right strips are cut off first.
+fn add_trim(usable_node: JsonCPNode, sheettype: &SheetType) -> JsonCPNode {
+    let trim = sheettype.trim();
+    let node = wrap_in_trim_strips(usable_node, JsonOrientation::H, (trim.top, trim.bottom), sheettype.kerf());
+    let node = wrap_in_trim_strips(node, JsonOrientation::V, (trim.left, trim.right), sheettype.kerf());
+    debug_assert!(node.length == sheettype.width() && node.height == sheettype.height());
+    node
+}
+
+fn wrap_in_trim_strips(node: JsonCPNode, orientation: JsonOrientation, margins: (u64, u64), kerf: u64) -> JsonCPNode {
+    if margins == (0, 0) {
+        return node;
+    }
+    let (fixed_dim, mut summed_dim) = match orientation {
+        JsonOrientation::H => (node.length, node.height),
+        JsonOrientation::V => (node.height, node.length),
+    };
+    //Position of the wrapper and the strips along the summed dimension
+    let (fixed_pos, node_pos) = match orientation {
+        JsonOrientation::H => (node.x, node.y),
+        JsonOrientation::V => (node.y, node.x),
+    };
+    let start_pos = match margins.0 > 0 {
+        true => node_pos - margins.0 - kerf,
+        false => node_pos,
+    };
+    let end_strip_pos = node_pos + summed_dim + kerf;
+    let strip = |margin: u64, pos: u64| {
+        let (length, height, x, y) = match orientation {
+            JsonOrientation::H => (fixed_dim, margin, fixed_pos, pos),
+            JsonOrientation::V => (margin, fixed_dim, pos, fixed_pos),
+        };
+        JsonCPNode { length, height, x, y, orientation: None, node_type: JsonCPNodeType::Trim, item: None, children: vec![] }
+    };
+
+    let mut children = Vec::new();
+    if margins.0 > 0 {
+        children.push(strip(margins.0, start_pos));
+        summed_dim += margins.0 + kerf;
+    }
+    children.push(node);
+    if margins.1 > 0 {
+        children.push(strip(margins.1, end_strip_pos));
+        summed_dim += margins.1 + kerf;
+    }
+
+    let (length, height, x, y) = match orientation {
+        JsonOrientation::H => (fixed_dim, summed_dim, fixed_pos, start_pos),
+        JsonOrientation::V => (summed_dim, fixed_dim, start_pos, fixed_pos),
+    };
+    JsonCPNode { length, height, x, y, orientation: Some(orientation), node_type: JsonCPNodeType::Structure, item: None, children }
+}
+
+/// Returns the node which covers the usable area of a sheet, skipping all trim strips, together with its path
+pub fn strip_trim(json_node: &JsonCPNode, path: String) -> Result<(&JsonCPNode, String), Violation> {
+    let is_trim = |n: &JsonCPNode| matches!(n.node_type, JsonCPNodeType::Trim);
+    match json_node.children.iter().any(is_trim) {
+        true => {
+            let mut untrimmed_children = json_node.children.iter().enumerate().filter(|(_, c)| !is_trim(c));
+            let (i, usable_node) = untrimmed_children.next().ok_or_else(|| Violation::new(&path, "node without usable area".to_string()))?;
+            if untrimmed_children.next().is_some() {
+                return Err(Violation::new(&path, "trim strips should surround a single node".to_string()));
+            }
+            strip_trim(usable_node, format!("{}.Children[{}]", path, i))
+        }
+        false => Ok((json_node, path)),
+    }
+}
+
+/// Inverse of convert_node_bp_to_json_cp_node, the kerf is added back to all nodes.
+/// Material lost to the kerf that is not accounted for by the children is restored as an empty node.
+pub fn convert_json_cp_node_to_node_bp(json_node: &JsonCPNode, parent_orientation: Option<Orientation>, kerf: u64, instance: &Instance, path: &str) -> Result<NodeBlueprint, Violation> {
+    //Only structure nodes define their orientation, all other nodes are cut perpendicular to their parent
+    let next_cut_orient = match (&json_node.orientation, parent_orientation) {
+        (Some(JsonOrientation::H), _) => Orientation::Horizontal,
+        (Some(JsonOrientation::V), _) => Orientation::Vertical,
+        (None, Some(parent_orientation)) => parent_orientation.rotate(),
+        (None, None) => return Err(Violation::new(path, "root node has no orientation".to_string())),
+    };
+
+    let parttype = match (&json_node.node_type, json_node.item) {
+        (JsonCPNodeType::Item, Some(item)) => {
+            if item >= instance.parts().len() {
+                return Err(Violation::new(path, format!("item {} does not exist", item)));
+            }
+            let parttype = instance.get_parttype(item);
+            let default_fit = json_node.length == parttype.width() && json_node.height == parttype.height();
+            let rotated_fit = json_node.length == parttype.height() && json_node.height == parttype.width() && parttype.fixed_rotation().is_none();
+            if !default_fit && !rotated_fit {
+                return Err(Violation::new(path, format!("item node [{}x{}] does not match item {} [{}x{}]",
+                    json_node.length, json_node.height, item, parttype.width(), parttype.height())));
+            }
+            Some(parttype)
+        }
+        (JsonCPNodeType::Item, None) => return Err(Violation::new(path, "item node without item reference".to_string())),
+        (_, _) => None,
+    };
+
+    let (width, height) = (json_node.length + kerf, json_node.height + kerf);
+    let mut node_bp = NodeBlueprint::new(width, height, parttype, next_cut_orient);
+    for (i, json_child) in json_node.children.iter().enumerate() {
+        let child_path = format!("{}.Children[{}]", path, i);
+        //Every child spans the full node in the direction parallel to the cuts
+        let spans_node = match next_cut_orient {
+            Orientation::Horizontal => json_child.length == json_node.length,
+            Orientation::Vertical => json_child.height == json_node.height,
+        };
+        if !spans_node {
+            return Err(Violation::new(&child_path, format!("child [{}x{}] does not span its parent [{}x{}]",
+                json_child.length, json_child.height, json_node.length, json_node.height)));
+        }
+        node_bp.add_child(convert_json_cp_node_to_node_bp(json_child, Some(next_cut_orient), kerf, instance, &child_path)?);
+    }
+
+    if !json_node.children.is_empty() {
+        let (summed_dim, sum_of_children) = match next_cut_orient {
+            Orientation::Horizontal => (height, node_bp.children().iter().map(|c| c.height()).sum::<u64>()),
+            Orientation::Vertical => (width, node_bp.children().iter().map(|c| c.width()).sum::<u64>()),
+        };
+        if sum_of_children > summed_dim {
+            return Err(Violation::new(path, format!("children of node [{}x{}] exceed its dimensions", json_node.length, json_node.height)));
+        }
+        let remainder = summed_dim - sum_of_children;
+        if remainder > 0 {
+            let remainder_bp = match next_cut_orient {
+                Orientation::Horizontal => NodeBlueprint::new(width, remainder, None, next_cut_orient.rotate()),
+                Orientation::Vertical => NodeBlueprint::new(remainder, height, None, next_cut_orient.rotate()),
+            };
+            node_bp.add_child(remainder_bp);
+        }
+    }
+    Ok(node_bp)
 }
\ No newline at end of file
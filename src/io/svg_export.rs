@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use svg::node::element::{Group, Rectangle, Text};
+use svg::Document;
+
+use crate::io::json_format::{JsonCP, JsonCPNode, JsonCPNodeType, JsonSheetType, JsonSolution};
+
+/// Defines what is drawn on the items of a cutting pattern
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    /// Reference of the item
+    pub labels: bool,
+    /// Length and height of the item
+    pub dimensions: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            labels: true,
+            dimensions: true,
+        }
+    }
+}
+
+/// Writes every cutting pattern of the solution to a standalone SVG file in the given directory, named after its index.
+/// Returns the paths of the written files.
+pub fn write_cutting_patterns(json_solution: &JsonSolution, directory: &Path, options: &SvgOptions) -> Vec<PathBuf> {
+    fs::create_dir_all(directory).expect("SVG directory could not be created");
+    json_solution.cutting_patterns.iter().enumerate()
+        .map(|(i, json_cp)| {
+            let path = directory.join(format!("pattern_{}.svg", i));
+            let svg = generate_cutting_pattern(json_cp, &json_solution.sheettypes[json_cp.object], options);
+            fs::write(&path, svg).expect("SVG file could not be written");
+            path
+        })
+        .collect()
+}
+
+/// Generates a scalable SVG document of a cutting pattern, items of the same parttype share the same colour
+pub fn generate_cutting_pattern(json_cp: &JsonCP, json_sheet: &JsonSheetType, options: &SvgOptions) -> String {
+    let kerf = json_sheet.kerf.unwrap_or(0);
+    let stroke_width = 0.002 * u64::max(json_cp.root.height, json_cp.root.length) as f64;
+    let mut document = Document::new()
+        .set("width", "100%")
+        .set("height", "100%")
+        .set(
+            "viewBox",
+            (
+                -stroke_width,
+                -stroke_width,
+                json_cp.root.length as f64 + stroke_width * 2.0,
+                json_cp.root.height as f64 + stroke_width * 2.0,
+            ),
+        );
+    let mut group = Group::new();
+
+    if kerf > 0 {
+        //All material not covered by a node is lost to the kerf
+        let kerf_rect = Rectangle::new()
+            .set("x", 0)
+            .set("y", 0)
+            .set("width", json_cp.root.length)
+            .set("height", json_cp.root.height)
+            .set("fill", "#595959");
+        group = group.add(kerf_rect);
+    }
+
+    let mut subgroups = Vec::new();
+
+    generate_node(&json_cp.root, &mut subgroups, stroke_width, options);
+    for rect in subgroups {
+        group = group.add(rect);
+    }
+    for defect in json_sheet.defects.iter().flatten() {
+        let defect_rect = Rectangle::new()
+            .set("x", defect.x)
+            .set("y", defect.y)
+            .set("width", defect.length)
+            .set("height", defect.height)
+            .set("fill", "#C00000")
+            .set("fill-opacity", 0.5)
+            .set("stroke", "#C00000")
+            .set("stroke-width", stroke_width.to_string());
+        group = group.add(defect_rect);
+    }
+    document = document.add(group);
+
+    let mut write_buffer = Vec::new();
+    {
+        svg::write(&mut write_buffer, &document).expect("Failed to write SVG");
+    }
+
+    std::str::from_utf8(&write_buffer)
+        .expect("Failed to convert to string")
+        .to_string()
+}
+
+/// A light colour per parttype, successive parttypes are spread over the colour wheel by the golden angle
+fn item_color(item: usize) -> String {
+    format!("hsl({:.0}, 60%, 75%)", (item as f64 * 137.508) % 360.0)
+}
+
+fn generate_node(
+    json_cp_node: &JsonCPNode,
+    groups: &mut Vec<Group>,
+    stroke_width: f64,
+    options: &SvgOptions,
+) {
+    match json_cp_node.children.is_empty() {
+        true => {
+            let color = match json_cp_node.node_type {
+                JsonCPNodeType::Structure => panic!("Structure node should have children"),
+                JsonCPNodeType::Item => item_color(json_cp_node.item.unwrap()),
+                JsonCPNodeType::Leftover => "#A9D18E".to_string(),
+                JsonCPNodeType::Trim => "#F4B183".to_string(),
+                JsonCPNodeType::Defect => "#7F7F7F".to_string(),
+            };
+            let (x, y) = (json_cp_node.x as f64, json_cp_node.y as f64);
+            let (width, height) = (json_cp_node.length as f64, json_cp_node.height as f64);
+            let mut group = Group::new();
+            let rect = Rectangle::new()
+                .set("x", x)
+                .set("y", y)
+                .set("width", width)
+                .set("height", height)
+                .set("fill", color)
+                .set("stroke", "black")
+                .set("stroke-width", stroke_width.to_string());
+            group = group.add(rect);
+
+            let label = match (options.labels, options.dimensions) {
+                (true, true) => Some(format!("{}: [{}x{}]", json_cp_node.item.unwrap_or_default(), json_cp_node.length, json_cp_node.height)),
+                (true, false) => Some(format!("{}", json_cp_node.item.unwrap_or_default())),
+                (false, true) => Some(format!("[{}x{}]", json_cp_node.length, json_cp_node.height)),
+                (false, false) => None,
+            };
+
+            if let (JsonCPNodeType::Item, Some(label)) = (&json_cp_node.node_type, label) {
+                let mut text = Text::new(label)
+                    .set("x", x + (width * 0.5))
+                    .set("y", y + (height * 0.5))
+                    .set("text-anchor", "middle")
+                    .set("dominant-baseline", "middle")
+                    .set("fill", "black");
+
+                if json_cp_node.height > json_cp_node.length {
+                    text = text.set(
+                        "transform",
+                        format!("rotate(-90 {} {})", x + (width * 0.5), y + (height * 0.5)),
+                    );
+                }
+                let font_size = f64::min(
+                    0.005 * u64::max(json_cp_node.height, json_cp_node.length) as f64,
+                    0.02 * u64::min(json_cp_node.height, json_cp_node.length) as f64,
+                );
+                text = text.set("font-size", format!("{}em", font_size));
+
+                group = group.add(text);
+            }
+            groups.push(group);
+        }
+        false => {
+            for child in &json_cp_node.children {
+                generate_node(child, groups, stroke_width, options);
+            }
+        }
+    }
+}
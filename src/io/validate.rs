@@ -0,0 +1,677 @@
+use std::fmt::{Display, Formatter};
+
+use crate::io::json_format::{JsonCP, JsonDefect, JsonCPNode, JsonCPNodeType, JsonInstance, JsonOrientation, JsonRotation, JsonSolution, JsonStageMode};
+use crate::optimization::config::Config;
+
+/// Validates a JsonSolution against the JsonInstance it was generated for.
+/// Unlike the functions in util::assertions, the validator works purely on the JSON representation
+/// and does not rely on any of the optimizer's datastructures.
+///
+/// Every violation is reported together with the path to the offending node,
+/// e.g. "CuttingPatterns[2].Root.Children[0].Children[1]".
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub(crate) fn new(path: &str, message: String) -> Self {
+        Self {
+            path: path.to_string(),
+            message,
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+pub fn validate(json_instance: &JsonInstance, json_solution: &JsonSolution, config: &Config) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut n_items_used = vec![0; json_instance.parttypes.len()];
+    let mut n_objects_used = vec![0; json_instance.sheettypes.len()];
+
+    for (i, json_cp) in json_solution.cutting_patterns.iter().enumerate() {
+        let path = format!("CuttingPatterns[{}]", i);
+        if json_cp.multiplicity == 0 {
+            violations.push(Violation::new(&path, "multiplicity of 0".to_string()));
+        }
+        match n_objects_used.get_mut(json_cp.object) {
+            Some(n_used) => {
+                //Every repetition of the pattern uses another object and produces the same items
+                *n_used += json_cp.multiplicity;
+                let mut n_items_in_pattern = vec![0; json_instance.parttypes.len()];
+                validate_cutting_pattern(json_instance, json_cp, config, &path, &mut n_items_in_pattern, &mut violations);
+                for (n_items, n_in_pattern) in n_items_used.iter_mut().zip(n_items_in_pattern) {
+                    *n_items += n_in_pattern * json_cp.multiplicity;
+                }
+            }
+            None => {
+                violations.push(Violation::new(&path, format!("object {} does not exist", json_cp.object)));
+            }
+        }
+    }
+
+    for (i, json_part) in json_instance.parttypes.iter().enumerate() {
+        if n_items_used[i] > json_part.demand {
+            violations.push(Violation::new(&format!("Items[{}]", i),
+                format!("demand exceeded: {} produced, {} demanded", n_items_used[i], json_part.demand)));
+        }
+    }
+
+    for (i, json_sheet) in json_instance.sheettypes.iter().enumerate() {
+        let stock = json_sheet.stock.unwrap_or(usize::MAX);
+        if n_objects_used[i] > stock {
+            violations.push(Violation::new(&format!("Objects[{}]", i),
+                format!("stock exceeded: {} used, {} in stock", n_objects_used[i], stock)));
+        }
+    }
+
+    violations
+}
+
+fn validate_cutting_pattern(json_instance: &JsonInstance, json_cp: &JsonCP, config: &Config, path: &str, n_items_used: &mut [usize], violations: &mut Vec<Violation>) {
+    let json_sheet = &json_instance.sheettypes[json_cp.object];
+    let root_path = format!("{}.Root", path);
+
+    if json_cp.root.length != json_sheet.length || json_cp.root.height != json_sheet.height {
+        violations.push(Violation::new(&root_path,
+            format!("root [{}x{}] does not match object {} [{}x{}]",
+                    json_cp.root.length, json_cp.root.height, json_cp.object, json_sheet.length, json_sheet.height)));
+    }
+
+    //Items need to stay clear of the trim strips and the kerf of the cuts which remove them
+    let kerf = json_sheet.kerf.or(config.kerf).unwrap_or(0);
+    let margin = |trim: Option<u64>| trim.filter(|t| *t > 0).map(|t| t + kerf);
+    let usable_area = (
+        margin(json_sheet.trim_left),
+        margin(json_sheet.trim_top),
+        margin(json_sheet.trim_right).map(|m| json_sheet.length.saturating_sub(m)),
+        margin(json_sheet.trim_bottom).map(|m| json_sheet.height.saturating_sub(m)),
+    );
+
+    let constraints = Constraints {
+        json_instance,
+        rotation_allowed: config.rotation_allowed,
+        first_cut: json_sheet.first_cut.clone(),
+        max_stages: json_sheet.max_stages.or(config.max_stages).unwrap_or(u8::MAX),
+        trimming_allowed: json_sheet.stage_mode.or(config.stage_mode) == Some(JsonStageMode::WithTrimming),
+        kerf,
+        object_size: (json_sheet.length, json_sheet.height),
+        usable_area,
+        min_waste_dimension: config.min_waste_dimension.unwrap_or(0),
+        defects: json_sheet.defects.as_deref().unwrap_or_default(),
+    };
+    validate_node(&constraints, &json_cp.root, (None, 0), (0, 0), &root_path, n_items_used, violations);
+    validate_placements(&constraints, json_cp, path, violations);
+
+    let n_items: usize = n_items_used.iter().sum();
+    let max_items = json_sheet.max_items.or(config.max_items).unwrap_or(usize::MAX);
+    if n_items > max_items {
+        violations.push(Violation::new(path, format!("{} items on object {}, at most {} allowed", n_items, json_cp.object, max_items)));
+    }
+    let n_part_types = n_items_used.iter().filter(|n| **n > 0).count();
+    let max_part_types = json_sheet.max_part_types.or(config.max_part_types).unwrap_or(usize::MAX);
+    if n_part_types > max_part_types {
+        violations.push(Violation::new(path, format!("{} distinct part types on object {}, at most {} allowed", n_part_types, json_cp.object, max_part_types)));
+    }
+}
+
+/// The constraints a cutting pattern is validated against
+struct Constraints<'a> {
+    json_instance: &'a JsonInstance,
+    rotation_allowed: bool,
+    first_cut: Option<JsonOrientation>,
+    max_stages: u8,
+    trimming_allowed: bool,
+    kerf: u64,
+    object_size: (u64, u64),
+    /// Left, top, right and bottom edge of the area within the trim margins, None for sides without trim
+    usable_area: (Option<u64>, Option<u64>, Option<u64>, Option<u64>),
+    min_waste_dimension: u64,
+    defects: &'a [JsonDefect],
+}
+
+/// Recursively validates a node and its children.
+/// `stage` contains the orientation and number of the last stage of cuts that was applied to reach this node.
+/// `position` contains the coordinates of the top left corner of the node on the object.
+fn validate_node(constraints: &Constraints, node: &JsonCPNode, stage: (Option<JsonOrientation>, u8), position: (u64, u64), path: &str, n_items_used: &mut [usize], violations: &mut Vec<Violation>) {
+    if (node.x, node.y) != position {
+        violations.push(Violation::new(path, format!("node is located at ({}, {}), but its position in the tree is ({}, {})", node.x, node.y, position.0, position.1)));
+    }
+    match (&node.node_type, node.children.is_empty()) {
+        (JsonCPNodeType::Structure, true) => {
+            violations.push(Violation::new(path, "structure node without children".to_string()));
+        }
+        (JsonCPNodeType::Item | JsonCPNodeType::Leftover | JsonCPNodeType::Trim | JsonCPNodeType::Defect, false) => {
+            violations.push(Violation::new(path, "non-structure node with children".to_string()));
+        }
+        (JsonCPNodeType::Item, true) => {
+            validate_item(constraints, node, position, path, n_items_used, violations);
+        }
+        (JsonCPNodeType::Leftover, true) if node.length > 0 && node.height > 0 && u64::min(node.length, node.height) < constraints.min_waste_dimension => {
+            violations.push(Violation::new(path, format!("leftover [{}x{}] is narrower than the minimum waste dimension of {}", node.length, node.height, constraints.min_waste_dimension)));
+        }
+        (JsonCPNodeType::Defect, true) if !constraints.defects.iter().any(|defect| overlaps(defect, node, position)) => {
+            violations.push(Violation::new(path, "defect node does not cover any defect of the object".to_string()));
+        }
+        (_, _) => {}
+    }
+
+    if node.children.is_empty() {
+        return;
+    }
+
+    let orientation = match node.orientation.as_ref() {
+        Some(orientation) => orientation,
+        None => {
+            violations.push(Violation::new(path, "node with children has no orientation".to_string()));
+            return;
+        }
+    };
+
+    let (fixed_dim, summed_dim, sum_of_children) = match orientation {
+        JsonOrientation::H => (node.length, node.height, node.children.iter().map(|c| c.height).sum::<u64>()),
+        JsonOrientation::V => (node.height, node.length, node.children.iter().map(|c| c.length).sum::<u64>()),
+    };
+
+    //Cuts only start a new stage if they are perpendicular to the previous stage.
+    //Trim cuts are made before the sheet is cut into pieces, so they do not count as a stage.
+    let n_trimmed_children = node.children.iter().filter(|c| matches!(c.node_type, JsonCPNodeType::Trim)).count();
+    let child_stage = match (node.children.len() - n_trimmed_children, &stage.0) {
+        (1, _) if sum_of_children == summed_dim || n_trimmed_children > 0 => stage.clone(),
+        (_, Some(prev_orientation)) if prev_orientation == orientation => stage.clone(),
+        //If the first stage does not have the required orientation, the required first stage was skipped
+        (_, None) if constraints.first_cut.as_ref().is_some_and(|first_cut| first_cut != orientation) => (Some(orientation.clone()), 2),
+        (_, _) => (Some(orientation.clone()), stage.1 + 1),
+    };
+    //A trimming cut beyond the last stage can only separate a single item from waste
+    let is_trimming_cut = constraints.trimming_allowed && child_stage.1 == constraints.max_stages.saturating_add(1)
+        && node.children.iter().all(|c| matches!(c.node_type, JsonCPNodeType::Item | JsonCPNodeType::Leftover))
+        && node.children.iter().filter(|c| matches!(c.node_type, JsonCPNodeType::Item)).count() <= 1;
+    if child_stage.1 > constraints.max_stages && stage.1 <= constraints.max_stages && !is_trimming_cut {
+        violations.push(Violation::new(path, format!("stage {} exceeds the maximum of {} stages", child_stage.1, constraints.max_stages)));
+    }
+
+    //All children need to span the full node perpendicular to the cuts and together fill the node along the cuts.
+    //Every cut removes one kerf of material, the last one can be (partially) left out if it coincides with the edge of the node.
+    for (i, child) in node.children.iter().enumerate() {
+        let child_fixed_dim = match orientation {
+            JsonOrientation::H => child.length,
+            JsonOrientation::V => child.height,
+        };
+        if child_fixed_dim != fixed_dim {
+            violations.push(Violation::new(&format!("{}.Children[{}]", path, i),
+                format!("child [{}x{}] does not span its parent [{}x{}] perpendicular to the {:?} cuts",
+                        child.length, child.height, node.length, node.height, orientation)));
+        }
+    }
+    //Trim strips are only cut off the edges of the object, with the configured margins
+    let mut strip_position = position;
+    for (i, child) in node.children.iter().enumerate() {
+        if matches!(child.node_type, JsonCPNodeType::Trim) && !is_trim_strip(constraints, child, strip_position, orientation) {
+            violations.push(Violation::new(&format!("{}.Children[{}]", path, i), "trim strip is not one of the trim margins of the object".to_string()));
+        }
+        match orientation {
+            JsonOrientation::H => strip_position.1 += child.height + constraints.kerf,
+            JsonOrientation::V => strip_position.0 += child.length + constraints.kerf,
+        }
+    }
+    let occupied = sum_of_children + (node.children.len() as u64 - 1) * constraints.kerf;
+    if occupied > summed_dim || occupied + constraints.kerf < summed_dim {
+        violations.push(Violation::new(path,
+            format!("children add up to {} along the {:?} cuts (kerf included), expected {}", occupied, orientation, summed_dim)));
+    }
+
+    let mut child_position = position;
+    for (i, child) in node.children.iter().enumerate() {
+        let child_path = format!("{}.Children[{}]", path, i);
+        validate_node(constraints, child, child_stage.clone(), child_position, &child_path, n_items_used, violations);
+        match orientation {
+            JsonOrientation::H => child_position.1 += child.height + constraints.kerf,
+            JsonOrientation::V => child_position.0 += child.length + constraints.kerf,
+        }
+    }
+}
+
+/// A trim strip lies along an edge of the object and is exactly as wide as the trim margin on that side.
+/// The left and right strips span the full object, the top and bottom strips the full width between them,
+/// so only the nodes wrapping the root of the pattern can contain trim strips.
+fn is_trim_strip(constraints: &Constraints, strip: &JsonCPNode, position: (u64, u64), orientation: &JsonOrientation) -> bool {
+    let (left, top, right, bottom) = constraints.usable_area;
+    let (length, height) = constraints.object_size;
+    let kerf = constraints.kerf;
+    match orientation {
+        JsonOrientation::V => position.1 == 0 && strip.height == height
+            && ((position.0 == 0 && left == Some(strip.length + kerf))
+            || right.is_some_and(|right| position.0 == right + kerf && position.0 + strip.length == length)),
+        JsonOrientation::H => position.0 == left.unwrap_or(0) && position.0 + strip.length == right.unwrap_or(length)
+            && ((position.1 == 0 && top == Some(strip.height + kerf))
+            || bottom.is_some_and(|bottom| position.1 == bottom + kerf && position.1 + strip.height == height)),
+    }
+}
+
+fn overlaps(defect: &JsonDefect, node: &JsonCPNode, position: (u64, u64)) -> bool {
+    position.0 < defect.x + defect.length && defect.x < position.0 + node.length
+        && position.1 < defect.y + defect.height && defect.y < position.1 + node.height
+}
+
+/// The Placements need to list every item of the tree, in the same order and at the same position
+fn validate_placements(constraints: &Constraints, json_cp: &JsonCP, path: &str, violations: &mut Vec<Violation>) {
+    let mut items = vec![];
+    collect_items(&json_cp.root, &mut items);
+    if items.len() != json_cp.placements.len() {
+        violations.push(Violation::new(path, format!("{} placements listed, the tree contains {} items", json_cp.placements.len(), items.len())));
+        return;
+    }
+    for (i, (placement, node)) in json_cp.placements.iter().zip(items).enumerate() {
+        let placement_path = format!("{}.Placements[{}]", path, i);
+        if (Some(placement.item), placement.x, placement.y, placement.length, placement.height) != (node.item, node.x, node.y, node.length, node.height) {
+            violations.push(Violation::new(&placement_path,
+                format!("placement of item {} [{}x{}] at ({}, {}) does not match the item node in the tree", placement.item, placement.length, placement.height, placement.x, placement.y)));
+        } else if let Some(json_part) = constraints.json_instance.parttypes.get(placement.item) {
+            let rotated = (placement.length, placement.height) != (json_part.length, json_part.height);
+            if placement.rotated != rotated {
+                violations.push(Violation::new(&placement_path, format!("placement of item {} is marked as {}rotated", placement.item, if placement.rotated { "" } else { "not " })));
+            }
+        }
+    }
+}
+
+fn collect_items<'a>(node: &'a JsonCPNode, items: &mut Vec<&'a JsonCPNode>) {
+    if matches!(node.node_type, JsonCPNodeType::Item) {
+        items.push(node);
+    }
+    for child in node.children.iter() {
+        collect_items(child, items);
+    }
+}
+
+fn validate_item(constraints: &Constraints, node: &JsonCPNode, position: (u64, u64), path: &str, n_items_used: &mut [usize], violations: &mut Vec<Violation>) {
+    let (left, top, right, bottom) = constraints.usable_area;
+    let trim_overlaps = [
+        ("left", left.is_some_and(|left| position.0 < left)),
+        ("top", top.is_some_and(|top| position.1 < top)),
+        ("right", right.is_some_and(|right| position.0 + node.length > right)),
+        ("bottom", bottom.is_some_and(|bottom| position.1 + node.height > bottom)),
+    ];
+    for (side, _) in trim_overlaps.iter().filter(|(_, overlaps)| *overlaps) {
+        violations.push(Violation::new(path, format!("item overlaps the {} trim margin of the object", side)));
+    }
+    for (i, defect) in constraints.defects.iter().enumerate() {
+        if overlaps(defect, node, position) {
+            violations.push(Violation::new(path, format!("item overlaps defect {} of the object", i)));
+        }
+    }
+
+    let item = match node.item {
+        Some(item) => item,
+        None => {
+            violations.push(Violation::new(path, "item node without item reference".to_string()));
+            return;
+        }
+    };
+    let json_part = match constraints.json_instance.parttypes.get(item) {
+        Some(json_part) => json_part,
+        None => {
+            violations.push(Violation::new(path, format!("item {} does not exist", item)));
+            return;
+        }
+    };
+    n_items_used[item] += 1;
+
+    let rotation_allowed = match json_part.rotation {
+        Some(rotation) => rotation == JsonRotation::Allowed,
+        None => constraints.rotation_allowed,
+    };
+
+    let default_fit = node.length == json_part.length && node.height == json_part.height;
+    let rotated_fit = node.length == json_part.height && node.height == json_part.length;
+
+    match (default_fit, rotated_fit) {
+        (true, _) => {}
+        (false, true) if rotation_allowed => {}
+        (false, true) => {
+            violations.push(Violation::new(path, format!("item {} is rotated, but rotation is not allowed", item)));
+        }
+        (false, false) => {
+            violations.push(Violation::new(path,
+                format!("node [{}x{}] does not match item {} [{}x{}]", node.length, node.height, item, json_part.length, json_part.height)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::io::json_format::JsonPlacement;
+
+    /// Items: 0 [2x4], 1 [5x6], 2 [5x10], 3 [4x2] with a fixed rotation
+    fn instance(object: Value) -> JsonInstance {
+        serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": [object],
+            "Items": [
+                {"Length": 2, "Height": 4, "Demand": 5, "Value": 8},
+                {"Length": 5, "Height": 6, "Demand": 5, "Value": 30},
+                {"Length": 5, "Height": 10, "Demand": 1, "Value": 50},
+                {"Length": 4, "Height": 2, "Demand": 5, "Value": 8, "Rotation": "Fixed"}
+            ]
+        })).unwrap()
+    }
+
+    fn object() -> Value {
+        json!({"Length": 10, "Height": 10, "Stock": null, "Cost": 100})
+    }
+
+    fn config(extra: Value) -> Config {
+        let mut config = json!({
+            "avgNodesRemoved": 6, "blinkRate": 0.01, "leftoverValuationPower": 2, "historyLength": 500,
+            "rotationAllowed": true, "nThreads": 1, "sheetValuationMode": "area"
+        });
+        config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    }
+
+    fn node(length: u64, height: u64, x: u64, y: u64, node_type: &str) -> Value {
+        json!({"Length": length, "Height": height, "X": x, "Y": y, "Type": node_type, "Children": []})
+    }
+
+    fn item(length: u64, height: u64, x: u64, y: u64, item: usize) -> Value {
+        let mut item_node = node(length, height, x, y, "Item");
+        item_node["Item"] = json!(item);
+        item_node
+    }
+
+    fn structure(length: u64, height: u64, x: u64, y: u64, orientation: &str, children: Vec<Value>) -> Value {
+        json!({"Length": length, "Height": height, "X": x, "Y": y, "Orientation": orientation, "Type": "Structure", "Children": children})
+    }
+
+    /// A solution with a single cutting pattern, its placements are derived from the tree
+    fn solution(json_instance: &JsonInstance, root: Value, multiplicity: usize) -> JsonSolution {
+        let root: JsonCPNode = serde_json::from_value(root).unwrap();
+        let mut items = vec![];
+        collect_items(&root, &mut items);
+        let placements = items.iter().map(|node| {
+            let json_part = &json_instance.parttypes[node.item.unwrap()];
+            JsonPlacement {
+                item: node.item.unwrap(),
+                x: node.x,
+                y: node.y,
+                length: node.length,
+                height: node.height,
+                rotated: (node.length, node.height) != (json_part.length, json_part.height),
+            }
+        }).collect();
+        let json_cp = JsonCP { object: 0, multiplicity, usage: 1.0, n_cuts: 0, cut_length: 0, root, cuts: vec![], placements };
+
+        serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": json_instance.sheettypes,
+            "Items": json_instance.parttypes,
+            "CuttingPatterns": [json_cp],
+            "Statistics": {"UsagePct": 100.0, "PartAreaIncludedPct": 100.0, "NObjectsUsed": 1, "MaterialCost": 100, "RunTimeMs": 0, "ConfigPath": ""}
+        })).unwrap()
+    }
+
+    fn violations(json_instance: &JsonInstance, json_solution: &JsonSolution, config: &Config) -> Vec<String> {
+        validate(json_instance, json_solution, config).iter().map(|v| v.to_string()).collect()
+    }
+
+    /// A strip with item 1 above item 0 (with a leftover next to it), followed by item 2
+    fn three_item_root() -> Value {
+        structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![
+                item(5, 6, 0, 0, 1),
+                structure(5, 4, 0, 6, "V", vec![item(2, 4, 0, 6, 0), node(3, 4, 2, 6, "Leftover")]),
+            ]),
+            item(5, 10, 5, 0, 2),
+        ])
+    }
+
+    #[test]
+    fn valid_solution() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Leftover")]),
+            item(5, 10, 5, 0, 2),
+        ]), 1);
+        assert!(violations(&json_instance, &json_solution, &config(json!({}))).is_empty());
+    }
+
+    #[test]
+    fn stage_count() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![
+                item(5, 6, 0, 0, 1),
+                structure(5, 4, 0, 6, "V", vec![item(2, 4, 0, 6, 0), node(3, 4, 2, 6, "Leftover")]),
+            ]),
+            item(5, 10, 5, 0, 2),
+        ]), 1);
+        assert!(violations(&json_instance, &json_solution, &config(json!({"maxStages": 3}))).is_empty());
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"maxStages": 2}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1]: stage 3 exceeds the maximum of 2 stages"]);
+    }
+
+    #[test]
+    fn child_spans() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), item(2, 4, 0, 6, 0)]),
+            item(5, 10, 5, 0, 2),
+        ]), 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1]: child [2x4] does not span its parent [5x10] perpendicular to the H cuts"]);
+    }
+
+    #[test]
+    fn demand_and_stock() {
+        let mut limited_object = object();
+        limited_object["Stock"] = json!(2);
+        let json_instance = instance(limited_object);
+        assert_eq!(violations(&json_instance, &solution(&json_instance, three_item_root(), 3), &config(json!({}))),
+                   vec!["Items[2]: demand exceeded: 3 produced, 1 demanded", "Objects[0]: stock exceeded: 3 used, 2 in stock"]);
+    }
+
+    #[test]
+    fn kerf_sums() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            item(5, 10, 0, 0, 2),
+            node(4, 10, 6, 0, "Leftover"),
+        ]), 1);
+        assert!(violations(&json_instance, &json_solution, &config(json!({"kerf": 1}))).is_empty());
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"kerf": 0}))),
+                   vec![
+                       "CuttingPatterns[0].Root: children add up to 9 along the V cuts (kerf included), expected 10",
+                       "CuttingPatterns[0].Root.Children[1]: node is located at (6, 0), but its position in the tree is (5, 0)",
+                   ]);
+
+        let mut object_with_kerf = object();
+        object_with_kerf["Kerf"] = json!(2);
+        let json_instance = instance(object_with_kerf);
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            item(5, 10, 0, 0, 2),
+            node(4, 10, 7, 0, "Leftover"),
+        ]), 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"kerf": 1}))),
+                   vec!["CuttingPatterns[0].Root: children add up to 11 along the V cuts (kerf included), expected 10"]);
+    }
+
+    #[test]
+    fn trim_margin_overlap() {
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Leftover")]),
+            item(5, 10, 5, 0, 2),
+        ]);
+        let mut trimmed_object = object();
+        trimmed_object["TrimLeft"] = json!(1);
+        let json_instance = instance(trimmed_object);
+        let json_solution = solution(&json_instance, root, 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[0]: item overlaps the left trim margin of the object"]);
+    }
+
+    #[test]
+    fn trim_strips() {
+        let mut trimmed_object = object();
+        trimmed_object["TrimLeft"] = json!(1);
+        let json_instance = instance(trimmed_object);
+        let root = structure(10, 10, 0, 0, "V", vec![
+            node(1, 10, 0, 0, "Trim"),
+            structure(9, 10, 1, 0, "V", vec![item(5, 10, 1, 0, 2), node(4, 10, 6, 0, "Leftover")]),
+        ]);
+        assert!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({}))).is_empty());
+
+        //Trim strips need to match the margins of the object
+        let root = structure(10, 10, 0, 0, "V", vec![
+            node(2, 10, 0, 0, "Trim"),
+            structure(8, 10, 2, 0, "V", vec![item(5, 10, 2, 0, 2), node(3, 10, 7, 0, "Leftover")]),
+        ]);
+        assert_eq!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[0]: trim strip is not one of the trim margins of the object"]);
+
+        //Trim strips within the pattern would give the cuts next to them a free stage
+        let json_instance = instance(object());
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Trim")]),
+            item(5, 10, 5, 0, 2),
+        ]);
+        assert_eq!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({"maxStages": 1}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1]: trim strip is not one of the trim margins of the object"]);
+    }
+
+    #[test]
+    fn defect_nodes() {
+        let mut defective_object = object();
+        defective_object["Defects"] = json!([{"X": 8, "Y": 8, "Length": 1, "Height": 1}]);
+        let json_instance = instance(defective_object);
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Leftover")]),
+            structure(5, 10, 5, 0, "H", vec![
+                node(5, 7, 5, 0, "Leftover"),
+                structure(5, 3, 5, 7, "V", vec![node(3, 3, 5, 7, "Leftover"), node(2, 3, 8, 7, "Defect")]),
+            ]),
+        ]);
+        assert!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({}))).is_empty());
+
+        //Defect nodes can only be used to discard the defects of the object
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Defect")]),
+            structure(5, 10, 5, 0, "H", vec![
+                node(5, 7, 5, 0, "Leftover"),
+                structure(5, 3, 5, 7, "V", vec![node(3, 3, 5, 7, "Leftover"), node(2, 3, 8, 7, "Defect")]),
+            ]),
+        ]);
+        assert_eq!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1]: defect node does not cover any defect of the object"]);
+    }
+
+    #[test]
+    fn rotation() {
+        let json_instance = instance(object());
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![
+                item(5, 6, 0, 0, 1),
+                structure(5, 4, 0, 6, "V", vec![
+                    structure(4, 4, 0, 6, "H", vec![item(4, 2, 0, 6, 0), node(4, 2, 0, 8, "Leftover")]),
+                    node(1, 4, 4, 6, "Leftover"),
+                ]),
+            ]),
+            item(5, 10, 5, 0, 2),
+        ]);
+        let json_solution = solution(&json_instance, root, 1);
+        assert!(violations(&json_instance, &json_solution, &config(json!({}))).is_empty());
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"rotationAllowed": false}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1].Children[0].Children[0]: item 0 is rotated, but rotation is not allowed"]);
+
+        //The rotation of an item overrides the config
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), structure(5, 4, 0, 6, "V", vec![item(2, 4, 0, 6, 3), node(3, 4, 2, 6, "Leftover")])]),
+            item(5, 10, 5, 0, 2),
+        ]);
+        assert_eq!(violations(&json_instance, &solution(&json_instance, root, 1), &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1].Children[0]: item 3 is rotated, but rotation is not allowed"]);
+    }
+
+    #[test]
+    fn defect_overlap() {
+        let root = structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Leftover")]),
+            item(5, 10, 5, 0, 2),
+        ]);
+        let mut defective_object = object();
+        defective_object["Defects"] = json!([{"X": 8, "Y": 8, "Length": 1, "Height": 1}]);
+        let json_instance = instance(defective_object);
+        let json_solution = solution(&json_instance, root, 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec!["CuttingPatterns[0].Root.Children[1]: item overlaps defect 0 of the object"]);
+    }
+
+    #[test]
+    fn multiplicity() {
+        let json_instance = instance(object());
+        assert_eq!(violations(&json_instance, &solution(&json_instance, three_item_root(), 0), &config(json!({}))),
+                   vec!["CuttingPatterns[0]: multiplicity of 0"]);
+    }
+
+    #[test]
+    fn trimming_stage() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, three_item_root(), 1);
+        //A trimming cut beyond the last stage only separates a single item from waste
+        assert!(violations(&json_instance, &json_solution, &config(json!({"maxStages": 2, "stageMode": "WithTrimming"}))).is_empty());
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"maxStages": 1, "stageMode": "WithTrimming"}))),
+                   vec!["CuttingPatterns[0].Root.Children[0]: stage 2 exceeds the maximum of 1 stages"]);
+    }
+
+    #[test]
+    fn min_waste_dimension() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, structure(10, 10, 0, 0, "V", vec![
+            structure(5, 10, 0, 0, "H", vec![item(5, 6, 0, 0, 1), node(5, 4, 0, 6, "Leftover")]),
+            item(5, 10, 5, 0, 2),
+        ]), 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"minWasteDimension": 5}))),
+                   vec!["CuttingPatterns[0].Root.Children[0].Children[1]: leftover [5x4] is narrower than the minimum waste dimension of 5"]);
+    }
+
+    #[test]
+    fn item_limits() {
+        let json_instance = instance(object());
+        let json_solution = solution(&json_instance, three_item_root(), 1);
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({"maxItems": 2, "maxPartTypes": 2}))),
+                   vec!["CuttingPatterns[0]: 3 items on object 0, at most 2 allowed",
+                        "CuttingPatterns[0]: 3 distinct part types on object 0, at most 2 allowed"]);
+        assert!(violations(&json_instance, &json_solution, &config(json!({"maxItems": 3, "maxPartTypes": 3}))).is_empty());
+    }
+
+    #[test]
+    fn coordinates_and_placements() {
+        let json_instance = instance(object());
+        let mut json_solution = solution(&json_instance, three_item_root(), 1);
+        json_solution.cutting_patterns[0].root.children[1].x = 0;
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec![
+                       "CuttingPatterns[0].Root.Children[1]: node is located at (0, 0), but its position in the tree is (5, 0)",
+                       "CuttingPatterns[0].Placements[2]: placement of item 2 [5x10] at (5, 0) does not match the item node in the tree",
+                   ]);
+
+        let mut json_solution = solution(&json_instance, three_item_root(), 1);
+        json_solution.cutting_patterns[0].placements[0].rotated = true;
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec!["CuttingPatterns[0].Placements[0]: placement of item 1 is marked as rotated"]);
+
+        json_solution.cutting_patterns[0].placements.pop();
+        assert_eq!(violations(&json_instance, &json_solution, &config(json!({}))),
+                   vec!["CuttingPatterns[0]: 2 placements listed, the tree contains 3 items"]);
+    }
+}
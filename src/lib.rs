@@ -2,6 +2,7 @@ use std::cmp::Ordering;
 use std::time::Instant;
 use once_cell::sync::Lazy;
 use crate::core::cost::Cost;
+use crate::optimization::objective::{Objective, PartAreaObjective};
 
 pub mod util;
 pub mod io;
@@ -10,10 +11,11 @@ pub mod core;
 
 
 pub static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+/// The comparison of the default objective, kept for compatibility
+#[deprecated(note = "use an Objective instead, this is PartAreaObjective without any secondary objectives")]
 pub const COST_COMPARATOR: fn(&Cost, &Cost) -> Ordering = |a: &Cost, b: &Cost| {
-    match a.part_area_excluded.cmp(&b.part_area_excluded) {
-        Ordering::Equal => a.leftover_value.partial_cmp(&b.leftover_value).unwrap().reverse(),
-        other => other
-    }
+    PartAreaObjective { minimize_patterns: false, group_orders: false, cut_tie_breaker: None }.compare(a, b)
 };
-pub const DETERMINISTIC_MODE: bool = false; //fixes seed
+/// No longer has any effect, kept for compatibility
+#[deprecated(note = "set the seed field of the Config instead")]
+pub const DETERMINISTIC_MODE: bool = false;
@@ -1,5 +1,11 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+use crate::io::csv_import::CsvImport;
+use crate::io::json_format::JsonStageMode;
+use crate::optimization::objective::{CutTieBreaker, KnapsackObjective, Objective, PartAreaObjective, WeightedObjective};
+
 /// Contains all the configurable parameters of the algorithm
 
 #[derive(Serialize, Deserialize)]
@@ -16,11 +22,68 @@ pub struct Config {
     pub n_threads: usize,
     pub sheet_valuation_mode : SheetValuationMode,
     pub max_stages: Option<u8>,
+    pub stage_mode: Option<JsonStageMode>,
+    pub max_items: Option<usize>,
+    pub max_part_types: Option<usize>,
+    pub seed: Option<u64>,
+    pub kerf: Option<u64>,
+    pub min_waste_dimension: Option<u64>,
+    pub min_remnant_length: Option<u64>,
+    pub min_remnant_height: Option<u64>,
+    #[serde(default)]
+    pub value_only_remnants: bool,
+    #[serde(default)]
+    pub objective: ObjectiveType,
+    pub cut_tie_breaker: Option<CutTieBreaker>,
+    #[serde(default)]
+    pub minimize_patterns: bool,
+    #[serde(default)]
+    pub group_orders: bool,
+    pub pattern_reduction_iterations: Option<usize>,
+    pub pattern_reduction_time: Option<usize>,
+    pub csv_import: Option<CsvImport>,
 }
 
-#[derive(Serialize, Deserialize)]
+impl Config {
+    pub fn objective(&self) -> Arc<dyn Objective> {
+        match &self.objective {
+            ObjectiveType::PartArea => Arc::new(PartAreaObjective { minimize_patterns: self.minimize_patterns, group_orders: self.group_orders, cut_tie_breaker: self.cut_tie_breaker }),
+            ObjectiveType::Knapsack => Arc::new(KnapsackObjective { minimize_patterns: self.minimize_patterns, group_orders: self.group_orders, cut_tie_breaker: self.cut_tie_breaker }),
+            ObjectiveType::Weighted(weighted_objective) => Arc::new(weighted_objective.clone()),
+        }
+    }
+
+    /// The minimum dimensions of a leftover to be reported as a reusable remnant, if configured
+    pub fn min_remnant_dimensions(&self) -> Option<(u64, u64)> {
+        match (self.min_remnant_length, self.min_remnant_height) {
+            (None, None) => None,
+            (length, height) => Some((length.unwrap_or(0), height.unwrap_or(0))),
+        }
+    }
+
+    /// The minimum dimensions of the remnants which are valued during the optimization, if only those should be valued
+    pub fn valued_remnant_dimensions(&self) -> Option<(u64, u64)> {
+        self.min_remnant_dimensions().filter(|_| self.value_only_remnants)
+    }
+}
+
+/// Defines how the sheets are valued.
+/// RemnantsFirst values sheets by area, except remnants, which are only valued at a fraction of their area
+/// and are filled before any other sheet is opened.
+#[derive(Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum SheetValuationMode {
     Area,
-    Cost
-}
\ No newline at end of file
+    Cost,
+    RemnantsFirst,
+}
+
+/// The objectives which can be selected in the config, see optimization::objective
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ObjectiveType {
+    #[default]
+    PartArea,
+    Knapsack,
+    Weighted(WeightedObjective),
+}
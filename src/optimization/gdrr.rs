@@ -1,5 +1,8 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool};
+use std::time::Instant;
 
 use colored::*;
 use itertools::Itertools;
@@ -11,14 +14,17 @@ use rand::rngs::SmallRng;
 use crate::core::cost::Cost;
 use crate::core::entities::parttype::PartType;
 use crate::core::insertion::insertion_blueprint::InsertionBlueprint;
+use crate::core::insertion::node_blueprint::NodeBlueprint;
 use crate::core::layout_index::LayoutIndex;
 use crate::core::leftover_valuator;
-use crate::optimization::config::Config;
+use crate::optimization::config::{Config, SheetValuationMode};
 use crate::optimization::instance::Instance;
+use crate::optimization::objective::{ExclusionMeasure, Objective};
 use crate::optimization::problem::Problem;
 use crate::optimization::rr::insertion_option_cache::InsertionOptionCache;
 use crate::optimization::sol_collectors::local_sol_collector::LocalSolCollector;
 use crate::optimization::solutions::problem_solution::ProblemSolution;
+use crate::optimization::solutions::sendable_solution::SendableSolution;
 use crate::optimization::solutions::solution::Solution;
 use crate::util::{assertions, blink};
 use crate::util::biased_sampler::{BiasedSampler, BiasMode};
@@ -31,32 +37,46 @@ pub struct GDRR<'a> {
     config: &'a Config,
     instance: &'a Instance,
     problem: Problem<'a>,
-    cost_comparator: fn(&Cost, &Cost) -> Ordering,
+    objective: Arc<dyn Objective>,
     local_sol_collector: LocalSolCollector<'a>,
 }
 
+/// Statistics of a single GDRR run
+#[derive(Debug, Clone)]
+pub struct GDRRStats {
+    pub n_iterations: usize,
+    pub n_accepted: usize,
+    pub n_improved: usize,
+}
 
 impl<'a> GDRR<'a> {
-    pub fn new(instance: &'a Instance, config: &'a Config, local_sol_collector: LocalSolCollector<'a>) -> Self {
-        let problem = Problem::new(instance);
+    pub fn new(instance: &'a Instance, config: &'a Config, local_sol_collector: LocalSolCollector<'a>, thread_index: usize) -> Self {
+        let mut problem = Problem::new(instance, config.seed, thread_index);
         leftover_valuator::set_power(config.leftover_valuation_power);
-        let cost_comparator = crate::COST_COMPARATOR;
+        leftover_valuator::set_valued_remnant_dimensions(config.valued_remnant_dimensions());
+        let objective = local_sol_collector.objective().clone();
+        problem.set_pattern_counting(objective.uses_n_patterns());
         Self {
             config,
             instance,
             problem,
-            cost_comparator,
+            objective,
             local_sol_collector,
         }
     }
 
+    /// Seeds the search with an existing solution, which will be used as the initial local optimum
+    pub fn warm_start(&mut self, solution: &SendableSolution) {
+        self.problem.restore_from_instance_solution(solution);
+    }
+
     // Late Acceptance Hill Climbing metaheuristic
-    pub fn lahc(&'a mut self) {
+    pub fn lahc(&'a mut self) -> GDRRStats {
         let start_time = std::time::Instant::now();
 
         let max_rr_iterations = self.config.max_rr_iterations.unwrap_or(usize::MAX);
 
-        let empty_problem_cost = Cost::new(0, 0.0, self.instance.total_part_area(), 0);
+        let empty_problem_cost = Cost::new(0, 0.0, self.instance.total_part_area(), 0, self.instance.total_part_value(), 0, 0);
 
         let mut lahc_history: VecDeque<Cost> = VecDeque::with_capacity(self.config.history_length);
         lahc_history.push_back(empty_problem_cost.clone());
@@ -66,6 +86,23 @@ impl<'a> GDRR<'a> {
         let mut mat_limit = self.local_sol_collector.material_limit();
         let mut local_optimum: Option<ProblemSolution> = None;
 
+        if !self.problem.layouts().is_empty() {
+            //The problem was warm started from an existing solution
+            let initial_solution = self.problem.create_solution(&None, None);
+            self.local_sol_collector.report_problem_solution(&initial_solution);
+            match initial_solution.is_complete() {
+                true => {
+                    //The initial solution defines the material limit, the search continues below it
+                    mat_limit = self.local_sol_collector.material_limit();
+                }
+                false => {
+                    lahc_history.clear();
+                    lahc_history.push_back(initial_solution.cost().clone());
+                    local_optimum = Some(initial_solution);
+                }
+            }
+        }
+
         while n_iterations < max_rr_iterations && !self.local_sol_collector.terminate() {
             let mat_limit_budget: i128 = match local_optimum.as_ref() {
                 Some(solution) => mat_limit as i128 - 1 - solution.cost().material_cost as i128,
@@ -73,24 +110,25 @@ impl<'a> GDRR<'a> {
             };
 
             let mat_limit_budget = self.ruin(mat_limit_budget);
-            let max_part_area_not_included = match local_optimum.as_ref() {
-                Some(local_optimum) => u64::max(lahc_history.front().unwrap().part_area_excluded, local_optimum.cost().part_area_excluded),
-                None => lahc_history.front().unwrap().part_area_excluded
+            let measure = self.objective.exclusion_measure();
+            let max_excluded = match local_optimum.as_ref() {
+                Some(local_optimum) => u64::max(measure.excluded(lahc_history.front().unwrap()), measure.excluded(local_optimum.cost())),
+                None => measure.excluded(lahc_history.front().unwrap())
             };
 
-            self.recreate(mat_limit_budget, max_part_area_not_included);
+            self.recreate(mat_limit_budget, max_excluded);
 
             let cost = self.problem.cost();
 
-            if (self.cost_comparator)(&cost, lahc_history.front().unwrap()) <= Ordering::Equal ||
-                (local_optimum.is_some() && (self.cost_comparator)(&cost, local_optimum.as_ref().unwrap().cost()) <= Ordering::Equal) {
+            if self.objective.compare(&cost, lahc_history.front().unwrap()) <= Ordering::Equal ||
+                (local_optimum.is_some() && self.objective.compare(&cost, local_optimum.as_ref().unwrap().cost()) <= Ordering::Equal) {
                 //Solution is better or equivalent to the last entry in the history queue or the local optimum.
 
                 local_optimum = Some(self.problem.create_solution(&local_optimum, Some(cost.clone())));
 
                 lahc_history.pop_front();
 
-                if (self.cost_comparator)(&cost, lahc_history.back().unwrap_or(&empty_problem_cost)) == Ordering::Less {
+                if self.objective.compare(&cost, lahc_history.back().unwrap_or(&empty_problem_cost)) == Ordering::Less {
                     //Current local optimum is better than the last value of the history queue
                     for _ in 0..(self.config.history_length - lahc_history.len()) {
                         lahc_history.push_back(cost.clone());
@@ -135,6 +173,85 @@ impl<'a> GDRR<'a> {
                 }
                 None => "()".to_string()
             });
+
+        GDRRStats {
+            n_iterations,
+            n_accepted,
+            n_improved,
+        }
+    }
+
+    /// Post-optimization phase which reduces the number of distinct cutting patterns of the warm started solution,
+    /// without increasing its material cost or excluding more parts.
+    /// Every iteration ruins all layouts of a pattern (biased towards rarely used ones) and recreates them,
+    /// by copying the remaining patterns first and inserting the leftover parts afterwards.
+    /// The phase ends early when the deadline passes or `running` is set to false (e.g. by a Ctrl+C handler).
+    pub fn reduce_patterns(&mut self, max_iterations: usize, deadline: Option<Instant>, running: &AtomicBool) -> ProblemSolution<'a> {
+        self.problem.set_pattern_counting(true);
+        let mut incumbent = self.problem.create_solution(&None, None);
+        let initial_n_patterns = incumbent.cost().n_patterns;
+
+        for _ in 0..max_iterations {
+            if !running.load(atomic::Ordering::SeqCst) || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let pattern_groups = self.problem.pattern_groups();
+            if pattern_groups.len() <= 1 {
+                break;
+            }
+
+            let entries = pattern_groups.iter().enumerate()
+                .map(|(i, group)| (i, group.len()))
+                .collect_vec();
+            let biased_sampler = BiasedSampler::new_default(entries, BiasMode::Low);
+            let selected_pattern = *biased_sampler.sample(self.problem.rng()).expect("no pattern selected");
+
+            for layout_index in &pattern_groups[selected_pattern] {
+                let top_node = *self.problem.layouts()[*layout_index].top_node_index();
+                self.problem.remove_node(top_node, LayoutIndex::Existing(*layout_index));
+            }
+
+            let mat_limit_budget = incumbent.cost().material_cost as i128 - self.problem.cost().material_cost as i128;
+            let mat_limit_budget = self.copy_patterns(mat_limit_budget);
+            self.recreate(mat_limit_budget, self.objective.exclusion_measure().excluded(incumbent.cost()));
+
+            let cost = self.problem.cost();
+            let incumbent_cost = incumbent.cost();
+            if cost.material_cost <= incumbent_cost.material_cost
+                && cost.part_area_excluded <= incumbent_cost.part_area_excluded
+                && cost.part_value_excluded <= incumbent_cost.part_value_excluded
+                && cost.n_patterns <= incumbent_cost.n_patterns
+                && (!self.config.group_orders || cost.order_spread <= incumbent_cost.order_spread) {
+                //Equivalent solutions are accepted as well, to allow the search to drift
+                incumbent = self.problem.create_solution(&Some(incumbent), Some(cost));
+            } else {
+                self.problem.restore_from_problem_solution(&incumbent);
+            }
+        }
+        timed_thread_println!("{}:\t {} -> {} distinct patterns", "Pattern reduction".bright_magenta(), initial_n_patterns, incumbent.cost().n_patterns);
+
+        incumbent
+    }
+
+    /// Adds copies of the existing patterns (most used first), as long as the parts, the stock and the budget allow it
+    fn copy_patterns(&mut self, mut mat_limit_budget: i128) -> i128 {
+        let mut pattern_groups = self.problem.pattern_groups();
+        pattern_groups.sort_by_key(|group| Reverse(group.len()));
+
+        for group in pattern_groups {
+            let layout = &self.problem.layouts()[group[0]];
+            let sheettype = layout.sheettype();
+            let parts = layout.get_included_parts().into_iter().counts();
+            let top_node = NodeBlueprint::from_node(*layout.top_node_index(), layout.nodes());
+
+            while self.problem.sheettype_qtys()[sheettype.id()] > 0
+                && mat_limit_budget >= sheettype.value() as i128
+                && parts.iter().all(|(parttype_id, qty)| self.problem.parttype_qtys()[*parttype_id] >= *qty) {
+                self.problem.restore_layout_from_blueprint(sheettype.id(), &top_node).expect("stock and demand were checked");
+                mat_limit_budget -= sheettype.value() as i128;
+            }
+        }
+        mat_limit_budget
     }
 
     fn ruin(&mut self, mut mat_limit_budget: i128) -> i128 {
@@ -196,14 +313,16 @@ impl<'a> GDRR<'a> {
         mat_limit_budget
     }
 
-    fn recreate(&mut self, mut mat_limit_budget: i128, max_part_area_excluded: u64) {
+    /// Inserts the missing parts until they are all included, or until more parts (as measured by the objective) are excluded than `max_excluded`
+    fn recreate(&mut self, mut mat_limit_budget: i128, max_excluded: u64) {
+        let measure = self.objective.exclusion_measure();
         let mut parttypes_to_consider: Vec<&PartType> = self.problem.parttype_qtys().iter().enumerate()
             .filter(|(_i, q)| { **q > 0 })
             .map(|(i, _q)| -> &PartType { self.problem.instance().get_parttype(i) }).collect();
 
 
         let mut insertion_option_cache = InsertionOptionCache::new(self.instance);
-        let mut part_area_not_included: u64 = 0;
+        let mut excluded: u64 = 0;
 
         //Collect all the layouts which should be considered during this recreate iteration
         let layouts_to_consider = self.problem.layouts().iter().map(|(i, l)| (LayoutIndex::Existing(i), l))
@@ -217,9 +336,9 @@ impl<'a> GDRR<'a> {
         insertion_option_cache.add_for_parttypes(&parttypes_to_consider, &layouts_to_consider);
         debug_assert!(assertions::insertion_option_cache_is_valid(&self.problem, &insertion_option_cache, &parttypes_to_consider));
 
-        while !parttypes_to_consider.is_empty() && part_area_not_included <= max_part_area_excluded {
-            let elected_parttype = GDRR::select_next_parttype(&parttypes_to_consider, &insertion_option_cache, self.problem.rng(), &self.config);
-            let elected_blueprint = GDRR::select_insertion_blueprint(elected_parttype, &insertion_option_cache, mat_limit_budget, &mut self.problem, &self.config, &self.cost_comparator);
+        while !parttypes_to_consider.is_empty() && excluded <= max_excluded {
+            let elected_parttype = GDRR::select_next_parttype(&parttypes_to_consider, &insertion_option_cache, measure, self.problem.rng(), &self.config);
+            let elected_blueprint = GDRR::select_insertion_blueprint(elected_parttype, &insertion_option_cache, mat_limit_budget, &mut self.problem, &self.config, self.objective.as_ref());
 
             if let Some(elected_blueprint) = elected_blueprint.as_ref() {
                 let cache_updates = self.problem.implement_insertion_blueprint(elected_blueprint);
@@ -252,8 +371,8 @@ impl<'a> GDRR<'a> {
                 debug_assert!(assertions::insertion_option_cache_is_valid(&self.problem, &insertion_option_cache, &parttypes_to_consider), "{:#?}\n{:#?}", elected_blueprint, cache_updates);
             } else {
                 //if there is no insertion blueprint, the part cannot be added to the problem
-                part_area_not_included += *self.problem.parttype_qtys().get(elected_parttype.id()).unwrap() as u64
-                    * elected_parttype.area();
+                excluded += *self.problem.parttype_qtys().get(elected_parttype.id()).unwrap() as u64
+                    * measure.of_parttype(elected_parttype);
 
                 parttypes_to_consider.retain(|pt| { pt.id() != elected_parttype.id() });
 
@@ -262,10 +381,23 @@ impl<'a> GDRR<'a> {
         }
     }
 
-    fn select_next_parttype(parttypes: &[&'a PartType], insertion_option_cache: &InsertionOptionCache<'a>, rand: &mut SmallRng, config: &Config) -> &'a PartType {
+    /// Selects the parttype with the fewest insertion options (with blinks).
+    /// When the objective measures the excluded parts by value, the parttypes with the highest value per unit of area are selected first.
+    fn select_next_parttype(parttypes: &[&'a PartType], insertion_option_cache: &InsertionOptionCache<'a>, measure: ExclusionMeasure, rand: &mut SmallRng, config: &Config) -> &'a PartType {
         let mut indices = (0..parttypes.len()).collect_vec();
         indices.shuffle(rand);
 
+        if measure == ExclusionMeasure::PartValue {
+            let n_options = |parttype: &PartType| insertion_option_cache.get_for_parttype(parttype).map_or(0, |options| options.len());
+            //Value densities are compared by cross-multiplication: v_a / a_a > v_b / a_b <=> v_a * a_b > v_b * a_a
+            indices.sort_by(|a, b| {
+                let (pt_a, pt_b) = (parttypes[*a], parttypes[*b]);
+                (pt_b.value() as u128 * pt_a.area() as u128).cmp(&(pt_a.value() as u128 * pt_b.area() as u128))
+                    .then(n_options(pt_a).cmp(&n_options(pt_b)))
+            });
+            return parttypes[indices[blink::select_lowest_in_range(0..indices.len(), config.blink_rate, rand)]];
+        }
+
         let n_options: Vec<usize> = indices.iter().map(|i| {
             let parttype = parttypes[*i];
             insertion_option_cache.get_for_parttype(parttype).map_or(0, |options| options.len())
@@ -276,10 +408,20 @@ impl<'a> GDRR<'a> {
         parttypes[parttype_index]
     }
 
-    fn select_insertion_blueprint(parttype: &'a PartType, insertion_option_cache: &InsertionOptionCache<'a>, mat_limit_budget: i128, problem: &mut Problem, config: &Config, cost_comparator: &fn(&Cost, &Cost) -> Ordering) -> Option<InsertionBlueprint<'a>> {
+    fn select_insertion_blueprint(parttype: &'a PartType, insertion_option_cache: &InsertionOptionCache<'a>, mat_limit_budget: i128, problem: &mut Problem, config: &Config, objective: &dyn Objective) -> Option<InsertionBlueprint<'a>> {
         let insertion_options = insertion_option_cache.get_for_parttype(parttype);
         match insertion_options {
             Some(options) => {
+                //When grouping orders, the layouts which already contain the order of the part are considered first
+                let options = match (config.group_orders, parttype.order()) {
+                    (true, Some(order)) => {
+                        options.iter()
+                            .sorted_by_key(|option| !problem.get_layout(option.layout_index()).contains_order(order))
+                            .collect_vec()
+                    }
+                    _ => options.iter().collect_vec(),
+                };
+
                 //Collect the blueprints
                 let mut existing_layout_blueprints: Vec<InsertionBlueprint<'a>> = Vec::new();
                 let mut new_layout_blueprints: Vec<InsertionBlueprint<'a>> = Vec::new();
@@ -303,7 +445,7 @@ impl<'a> GDRR<'a> {
                     false => {
                         //Sort the blueprints by cost
                         existing_layout_blueprints.sort_by(|a, b| {
-                            cost_comparator(a.cost(), b.cost())
+                            objective.compare(a.cost(), b.cost())
                         });
                         //Select the best (blinked) one
                         let selected_blinked_index = blink::select_lowest_in_range(0..existing_layout_blueprints.len(), config.blink_rate, problem.rng());
@@ -317,6 +459,16 @@ impl<'a> GDRR<'a> {
                                 None
                             }
                             false => {
+                                //Fill the remnants in stock before opening any other sheet
+                                if config.sheet_valuation_mode == SheetValuationMode::RemnantsFirst {
+                                    let uses_remnant = |blueprint: &InsertionBlueprint| match blueprint.layout_index() {
+                                        LayoutIndex::Empty(i) => problem.empty_layouts()[*i].sheettype().is_remnant(),
+                                        LayoutIndex::Existing(_) => false,
+                                    };
+                                    if new_layout_blueprints.iter().any(uses_remnant) {
+                                        new_layout_blueprints.retain(uses_remnant);
+                                    }
+                                }
                                 //Select a random blueprint from the new layout blueprints
                                 let selected_index = problem.rng().random_range(0..new_layout_blueprints.len());
                                 Some(new_layout_blueprints.remove(selected_index))
@@ -8,6 +8,7 @@ pub struct Instance {
     parts: Vec<(PartType, usize)>,
     sheets: Vec<(SheetType, usize)>,
     total_part_area: u64,
+    total_part_value: u64,
     total_part_qty: usize,
 }
 
@@ -17,12 +18,14 @@ impl Instance {
         assert!(assertions::instance_parttypes_and_sheettypes_ids_correct(&parts, &sheets));
 
         let total_part_area = parts.iter().map(|(parttype, qty)| parttype.area() * (*qty as u64)).sum();
+        let total_part_value = parts.iter().map(|(parttype, qty)| parttype.value() * (*qty as u64)).sum();
         let total_part_qty = parts.iter().map(|(_, qty)| *qty).sum();
 
         Self {
             parts,
             sheets,
             total_part_area,
+            total_part_value,
             total_part_qty,
         }
     }
@@ -65,6 +68,10 @@ impl Instance {
         self.total_part_area
     }
 
+    pub fn total_part_value(&self) -> u64 {
+        self.total_part_value
+    }
+
     pub fn total_part_qty(&self) -> usize {
         self.total_part_qty
     }
@@ -4,4 +4,6 @@ pub mod rr;
 pub mod sol_collectors;
 pub mod instance;
 pub mod config;
+pub mod objective;
 pub mod solutions;
+pub mod solver;
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::cost::Cost;
+use crate::core::entities::parttype::PartType;
+
+/// The number of terms an Objective can compute from a Cost
+pub const N_OBJECTIVE_TERMS: usize = 6;
+
+/// Defines which solutions (and insertions) are preferred during the optimization.
+/// An objective computes its own terms from a Cost, in order of priority, lower terms are better.
+/// Costs are compared by their terms lexicographically, unless `compare` is overridden.
+///
+/// Completeness of a solution and the material limit are handled separately, by the GDRR algorithm itself.
+pub trait Objective: Send + Sync {
+    fn terms(&self, cost: &Cost) -> [f64; N_OBJECTIVE_TERMS];
+
+    /// Ordering::Less means that `a` is better than `b`
+    fn compare(&self, a: &Cost, b: &Cost) -> Ordering {
+        let (terms_a, terms_b) = (self.terms(a), self.terms(b));
+        terms_a.iter().zip(terms_b.iter())
+            .map(|(ta, tb)| ta.total_cmp(tb))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Whether the terms depend on the number of distinct patterns, which is only counted if required
+    fn uses_n_patterns(&self) -> bool {
+        true
+    }
+
+    /// How the parts which are excluded from a solution are measured during the recreate phase
+    fn exclusion_measure(&self) -> ExclusionMeasure {
+        ExclusionMeasure::PartArea
+    }
+}
+
+/// Measures the excluded parts by their area or by their value.
+/// The recreate phase stops once more parts are excluded than in the solutions it competes with,
+/// and in PartValue mode, the most valuable parts (per unit of area) are inserted first.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExclusionMeasure {
+    PartArea,
+    PartValue,
+}
+
+impl ExclusionMeasure {
+    pub fn excluded(&self, cost: &Cost) -> u64 {
+        match self {
+            ExclusionMeasure::PartArea => cost.part_area_excluded,
+            ExclusionMeasure::PartValue => cost.part_value_excluded,
+        }
+    }
+
+    pub fn of_parttype(&self, parttype: &PartType) -> u64 {
+        match self {
+            ExclusionMeasure::PartArea => parttype.area(),
+            ExclusionMeasure::PartValue => parttype.value(),
+        }
+    }
+}
+
+/// Which property of the cuts is minimized to break ties between equally good costs
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum CutTieBreaker {
+    NCuts,
+    CutLength,
+}
+
+fn pattern_term(minimize_patterns: bool, cost: &Cost) -> f64 {
+    match minimize_patterns {
+        true => cost.n_patterns as f64,
+        false => 0.0,
+    }
+}
+
+fn order_term(group_orders: bool, cost: &Cost) -> f64 {
+    match group_orders {
+        true => cost.order_spread as f64,
+        false => 0.0,
+    }
+}
+
+fn cut_term(cut_tie_breaker: Option<CutTieBreaker>, cost: &Cost) -> f64 {
+    match cut_tie_breaker {
+        None => 0.0,
+        Some(CutTieBreaker::NCuts) => cost.n_cuts as f64,
+        Some(CutTieBreaker::CutLength) => cost.cut_length as f64,
+    }
+}
+
+/// Maximizes the area of the included parts (the material cost is minimized by the GDRR algorithm itself),
+/// then minimizes the number of distinct patterns, the order spread and the cuts (if configured) and finally maximizes the value of the leftovers
+pub struct PartAreaObjective {
+    pub minimize_patterns: bool,
+    pub group_orders: bool,
+    pub cut_tie_breaker: Option<CutTieBreaker>,
+}
+
+impl Objective for PartAreaObjective {
+    fn terms(&self, cost: &Cost) -> [f64; N_OBJECTIVE_TERMS] {
+        [cost.part_area_excluded as f64, pattern_term(self.minimize_patterns, cost), order_term(self.group_orders, cost), cut_term(self.cut_tie_breaker, cost), -cost.leftover_value as f64, 0.0]
+    }
+
+    fn uses_n_patterns(&self) -> bool {
+        self.minimize_patterns
+    }
+}
+
+/// Maximizes the value of the included parts, then minimizes the material cost, the number of distinct patterns,
+/// the order spread and the cuts (if configured) and finally maximizes the value of the leftovers
+pub struct KnapsackObjective {
+    pub minimize_patterns: bool,
+    pub group_orders: bool,
+    pub cut_tie_breaker: Option<CutTieBreaker>,
+}
+
+impl Objective for KnapsackObjective {
+    fn terms(&self, cost: &Cost) -> [f64; N_OBJECTIVE_TERMS] {
+        [cost.part_value_excluded as f64, cost.material_cost as f64, pattern_term(self.minimize_patterns, cost), order_term(self.group_orders, cost), cut_term(self.cut_tie_breaker, cost), -cost.leftover_value as f64]
+    }
+
+    fn uses_n_patterns(&self) -> bool {
+        self.minimize_patterns
+    }
+
+    fn exclusion_measure(&self) -> ExclusionMeasure {
+        ExclusionMeasure::PartValue
+    }
+}
+
+/// Aggregates all cost components into a single weighted sum
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WeightedObjective {
+    pub material_cost: f64,
+    pub leftover_value: f64,
+    pub part_area_excluded: f64,
+    pub part_value_excluded: f64,
+    pub n_cuts: f64,
+    pub cut_length: f64,
+    pub n_patterns: f64,
+    pub order_spread: f64,
+}
+
+impl Objective for WeightedObjective {
+    fn terms(&self, cost: &Cost) -> [f64; N_OBJECTIVE_TERMS] {
+        let weighted_sum = self.material_cost * cost.material_cost as f64
+            - self.leftover_value * cost.leftover_value as f64
+            + self.part_area_excluded * cost.part_area_excluded as f64
+            + self.part_value_excluded * cost.part_value_excluded as f64
+            + self.n_cuts * cost.n_cuts as f64
+            + self.cut_length * cost.cut_length as f64
+            + self.n_patterns * cost.n_patterns as f64
+            + self.order_spread * cost.order_spread as f64;
+        [weighted_sum, 0.0, 0.0, 0.0, 0.0, 0.0]
+    }
+
+    fn uses_n_patterns(&self) -> bool {
+        self.n_patterns != 0.0
+    }
+
+    fn exclusion_measure(&self) -> ExclusionMeasure {
+        match self.part_area_excluded == 0.0 && self.part_value_excluded != 0.0 {
+            true => ExclusionMeasure::PartValue,
+            false => ExclusionMeasure::PartArea,
+        }
+    }
+}
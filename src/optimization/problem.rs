@@ -1,19 +1,24 @@
+use std::sync::Arc;
+
 use generational_arena::{Arena, Index};
+use indexmap::IndexMap;
+use itertools::Itertools;
 use rand::SeedableRng;
 use rand::rngs::SmallRng;
 
 use crate::core::cost::Cost;
 use crate::core::entities::layout::Layout;
 use crate::core::insertion::insertion_blueprint::InsertionBlueprint;
+use crate::core::insertion::node_blueprint::{NodeBlueprint, PatternKey};
 use crate::core::layout_index::LayoutIndex;
 use crate::core::orientation::Orientation;
-use crate::DETERMINISTIC_MODE;
 use crate::optimization::instance::Instance;
 use crate::optimization::rr::cache_updates::IOCUpdates;
 use crate::optimization::solutions::problem_solution::ProblemSolution;
 use crate::optimization::solutions::sendable_solution::SendableSolution;
 use crate::optimization::solutions::solution::Solution;
 use crate::util::assertions;
+use crate::util::util;
 
 /// Problem is the main representation of the optimization problem.
 /// A Problem is based on an Instance and contains a collection of Layouts.
@@ -30,15 +35,18 @@ pub struct Problem<'a> {
     solution_id_changed_layouts: Option<usize>,
     solution_id_counter: usize,
     layout_id_counter: usize,
+    /// Whether the cost includes the number of distinct patterns, see set_pattern_counting
+    count_patterns: bool,
 }
 
 impl<'a> Problem<'a> {
-    pub fn new(instance: &'a Instance) -> Self {
+    /// If a seed is provided, every thread_index results in a distinct but reproducible random stream
+    pub fn new(instance: &'a Instance, seed: Option<u64>, thread_index: usize) -> Self {
         let parttype_qtys = instance.parts().iter().map(|(_, qty)| *qty).collect::<Vec<_>>();
         let sheettype_qtys = instance.sheets().iter().map(|(_, qty)| *qty).collect::<Vec<_>>();
-        let random = match DETERMINISTIC_MODE {
-            true => SmallRng::seed_from_u64(0),
-            false => SmallRng::from_rng(&mut rand::rng())
+        let random = match seed {
+            Some(seed) => SmallRng::seed_from_u64(util::thread_seed(seed, thread_index)),
+            None => SmallRng::from_rng(&mut rand::rng())
         };
 
         let mut problem = Problem {
@@ -52,10 +60,11 @@ impl<'a> Problem<'a> {
             rng: random,
             solution_id_counter : 0,
             layout_id_counter : 0,
+            count_patterns : true,
         };
 
-        //Initiate the empty layouts
-        for (sheettype, _) in instance.sheets() {
+        //Initiate the empty layouts, sheettypes without stock (e.g. entirely covered by defects) are never used
+        for (sheettype, _) in instance.sheets().iter().filter(|(_, qty)| *qty > 0) {
             match sheettype.fixed_first_cut_orientation() {
                 Some(orientation) => {
                     let empty_layout = Layout::new(problem.next_layout_id(), sheettype, orientation);
@@ -95,8 +104,15 @@ impl<'a> Problem<'a> {
                 let empty_layout_clone = empty_layout.clone_with_id(next_layout_id);
                 let clone_index = self.register_layout(empty_layout_clone);
 
-                //Implement the blueprint
+                //Empty layouts of sheettypes with defects contain multiple empty nodes, those that remain need to be added to the cache
                 let mut cache_updates = IOCUpdates::new(LayoutIndex::Existing(clone_index));
+                let remaining_empty_nodes = self.layouts[clone_index].sorted_empty_nodes().iter()
+                    .filter(|n| *n != blueprint.original_node_index())
+                    .copied()
+                    .collect_vec();
+                cache_updates.extend_new(remaining_empty_nodes);
+
+                //Implement the blueprint
                 self.layouts[clone_index].implement_insertion_blueprint(blueprint, self.instance, &mut cache_updates);
 
                 cache_updates
@@ -115,19 +131,22 @@ impl<'a> Problem<'a> {
                         //Remove the entire layout
                         Some(self.unregister_layout(layout_index))
                     }
-                    false => {
-                        let removed_part_ids = layout.remove_node(node_index);
-                        for p_id in removed_part_ids {
-                            self.unregister_part(p_id, 1);
-                        }
+                    false => match layout.remove_node(node_index) {
+                        Some(removed_part_ids) => {
+                            for p_id in removed_part_ids {
+                                self.unregister_part(p_id, 1);
+                            }
 
-                        if self.get_layout(&layout_index).is_empty() {
-                            Some(self.unregister_layout(layout_index))
-                        }
-                        else {
-                            None
+                            if self.get_layout(&layout_index).is_empty() {
+                                Some(self.unregister_layout(layout_index))
+                            }
+                            else {
+                                None
+                            }
                         }
-                    }
+                        //The node cannot be removed without leaving unmachinable waste behind, the entire layout is removed instead
+                        None => Some(self.unregister_layout(layout_index)),
+                    },
                 }
             }
         }
@@ -142,9 +161,30 @@ impl<'a> Problem<'a> {
 
         cost.part_area_included = self.instance.total_part_area() - cost.part_area_excluded;
 
+        cost.part_value_excluded = self.parttype_qtys.iter().enumerate()
+            .fold(0, |acc, (id, qty)| acc + self.instance().get_parttype(id).value() * (*qty as u64));
+
+        if self.count_patterns {
+            cost.n_patterns = self.pattern_groups().len() as u64;
+        }
+
         cost
     }
 
+    /// Grouping the layouts into patterns is expensive, so it can be disabled when the number of patterns is not needed
+    pub fn set_pattern_counting(&mut self, count_patterns: bool) {
+        self.count_patterns = count_patterns;
+    }
+
+    /// Groups the indices of the layouts which share the same cutting pattern, in order of first occurrence
+    pub fn pattern_groups(&mut self) -> Vec<Vec<Index>> {
+        let mut groups: IndexMap<Arc<PatternKey>, Vec<Index>> = IndexMap::new();
+        for (index, layout) in self.layouts.iter_mut() {
+            groups.entry(layout.pattern_key()).or_default().push(index);
+        }
+        groups.into_values().collect()
+    }
+
     pub fn create_solution(&mut self, old_solution: &Option<ProblemSolution<'a>>, cached_cost: Option<Cost>) -> ProblemSolution<'a> {
         //TODO: implement cached cost for problem
 
@@ -229,8 +269,46 @@ impl<'a> Problem<'a> {
         self.reset_changed_layouts(solution.id());
     }
 
-    pub fn restore_from_instance_solution(&mut self, _solution: &SendableSolution) {
-        todo!()
+    /// Restores the problem from a solution which did not originate from this problem (e.g. another thread or a previous run).
+    /// All layouts are rebuilt from their blueprints.
+    pub fn restore_from_instance_solution(&mut self, solution: &SendableSolution) {
+        debug_assert!(std::ptr::eq(solution.instance().as_ref(), self.instance));
+
+        self.layouts.clear();
+        self.parttype_qtys = self.instance.parts().iter().map(|(_, qty)| *qty).collect();
+        self.sheettype_qtys = self.instance.sheets().iter().map(|(_, qty)| *qty).collect();
+
+        for sendable_layout in solution.layouts() {
+            let layout = sendable_layout.convert_to_layout(self.next_layout_id(), self.instance);
+            self.register_restored_layout(layout).expect("solution should fit the instance");
+        }
+
+        debug_assert!(self.parttype_qtys == *solution.parttype_qtys());
+        debug_assert!(self.sheettype_qtys == *solution.sheettype_qtys());
+
+        self.changed_layouts.clear();
+        self.solution_id_changed_layouts = None;
+    }
+
+    /// Rebuilds a layout from the blueprint of its top node and adds it to the problem.
+    /// Fails if the stock of the sheettype or the demand of one of its parttypes would be exceeded, the layout is not added in that case.
+    pub fn restore_layout_from_blueprint(&mut self, sheettype_id: usize, top_node: &NodeBlueprint) -> Result<Index, String> {
+        let sheettype = self.instance.get_sheettype(sheettype_id);
+        let layout = Layout::from_blueprint(self.next_layout_id(), sheettype, top_node, self.instance);
+        self.register_restored_layout(layout)
+    }
+
+    fn register_restored_layout(&mut self, layout: Layout<'a>) -> Result<Index, String> {
+        let sheettype_id = layout.sheettype().id();
+        if self.sheettype_qtys[sheettype_id] == 0 {
+            return Err(format!("stock of object {} exceeded", sheettype_id));
+        }
+        for (parttype_id, qty) in layout.get_included_parts().into_iter().counts() {
+            if self.parttype_qtys[parttype_id] < qty {
+                return Err(format!("demand of item {} exceeded", parttype_id));
+            }
+        }
+        Ok(self.register_layout(layout))
     }
 
     pub fn usage(&self) -> f64 {
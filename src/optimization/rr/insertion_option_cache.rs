@@ -6,6 +6,7 @@ use itertools::Itertools;
 use crate::core::entities::layout::Layout;
 use crate::core::entities::node::Node;
 use crate::core::entities::parttype::PartType;
+use crate::core::entities::sheettype::SheetType;
 use crate::core::insertion::insertion_option::InsertionOption;
 use crate::core::layout_index::LayoutIndex;
 use crate::core::rotation::Rotation;
@@ -39,7 +40,7 @@ impl<'a : 'b, 'b> InsertionOptionCache<'a> {
         let layout = problem.get_layout(layout_i);
         cache_updates.new_nodes().iter().for_each(|node_i| {
             let node = &layout.nodes()[*node_i];
-            self.add_for_node(node_i, node, layout_i, parttypes.iter());
+            self.add_for_node(node_i, node, layout_i, layout.sheettype(), parttypes.iter());
         });
     }
 
@@ -72,7 +73,7 @@ impl<'a : 'b, 'b> InsertionOptionCache<'a> {
                         //The empty node is smaller than this parttype. For the next (smaller) empty node, start searching from next index
                         starting_index = i + 1;
                     } else {
-                        let insertion_option = InsertionOptionCache::generate_insertion_option(empty_node, parttype, *layout_i, *empty_node_i);
+                        let insertion_option = InsertionOptionCache::generate_insertion_option(empty_node, parttype, layout.sheettype(), *layout_i, *empty_node_i);
                         match insertion_option {
                             Some(insertion_option) => {
                                 let insertion_option = Rc::new(insertion_option);
@@ -92,12 +93,12 @@ impl<'a : 'b, 'b> InsertionOptionCache<'a> {
         }
     }
 
-    pub fn add_for_node<I>(&mut self, node_i: &Index, node: &Node, layout_i: &LayoutIndex, parttypes: I)
+    pub fn add_for_node<I>(&mut self, node_i: &Index, node: &Node, layout_i: &LayoutIndex, sheettype: &SheetType, parttypes: I)
         where I: Iterator<Item=&'b &'a PartType> {
         if node.parttype().is_none() && node.children().is_empty() {
             for parttype in parttypes.into_iter() {
                 let insertion_option =
-                    InsertionOptionCache::generate_insertion_option(node, parttype, *layout_i, *node_i);
+                    InsertionOptionCache::generate_insertion_option(node, parttype, sheettype, *layout_i, *node_i);
                 match insertion_option {
                     Some(insertion_option) => {
                         let insertion_option = Rc::new(insertion_option);
@@ -132,17 +133,17 @@ impl<'a : 'b, 'b> InsertionOptionCache<'a> {
         }
     }
 
-    fn generate_insertion_option(node: &Node, parttype: &'a PartType, layout_i: LayoutIndex, node_i: Index) -> Option<InsertionOption<'a>> {
+    fn generate_insertion_option(node: &Node, parttype: &'a PartType, sheettype: &SheetType, layout_i: LayoutIndex, node_i: Index) -> Option<InsertionOption<'a>> {
         match parttype.fixed_rotation() {
             Some(fixed_rotation) => {
-                match node.insertion_possible(parttype, *fixed_rotation) {
+                match node.insertion_possible(parttype, *fixed_rotation, sheettype) {
                     true => Some(InsertionOption::new(layout_i, node_i, parttype, Some(*fixed_rotation))),
                     false => None
                 }
             }
             None => {
-                let default_possible = node.insertion_possible(parttype, Rotation::Default);
-                let rotated_possible = node.insertion_possible(parttype, Rotation::Rotated);
+                let default_possible = node.insertion_possible(parttype, Rotation::Default, sheettype);
+                let rotated_possible = node.insertion_possible(parttype, Rotation::Rotated, sheettype);
                 match (default_possible, rotated_possible) {
                     (true, true) => {
                         Some(InsertionOption::new(layout_i, node_i, parttype, None))
@@ -9,7 +9,9 @@ use colored::*;
 
 use crate::core::cost::Cost;
 use crate::optimization::config::Config;
+use crate::optimization::gdrr::GDRRStats;
 use crate::optimization::instance::Instance;
+use crate::optimization::objective::Objective;
 use crate::optimization::solutions::sendable_solution::SendableSolution;
 use crate::optimization::solutions::solution::Solution;
 use crate::optimization::solutions::solution_stats::SolutionStats;
@@ -29,7 +31,7 @@ pub struct GlobalSolCollector {
     best_complete_solution: Option<SendableSolution>,
     best_incomplete_solution: Option<SendableSolution>,
     best_incomplete_cost: Option<Cost>,
-    cost_comparator: fn(&Cost, &Cost) -> Ordering,
+    objective: Arc<dyn Objective>,
     material_limit: Option<u64>,
     tx_syncs: Vec<Sender<SyncMessage>>,
     rx_solution_report: Receiver<SolutionReportMessage>,
@@ -40,7 +42,7 @@ impl GlobalSolCollector {
                config: Arc<Config>,
                tx_syncs: Vec<Sender<SyncMessage>>,
                rx_solution_report: Receiver<SolutionReportMessage>,
-               cost_comparator: fn(&Cost, &Cost) -> Ordering,
+               objective: Arc<dyn Objective>,
     ) -> Self {
         Self {
             _instance,
@@ -48,22 +50,18 @@ impl GlobalSolCollector {
             best_complete_solution : None,
             best_incomplete_solution : None,
             best_incomplete_cost : None,
-            cost_comparator,
+            objective,
             material_limit : None,
             tx_syncs,
             rx_solution_report,
         }
     }
 
-    pub fn monitor(&mut self, gdrr_thread_handlers: Vec<thread::JoinHandle<()>>) {
+    /// Monitors the GDRR threads until a termination condition is met or `running` is set to false.
+    /// Returns the statistics of all threads.
+    pub fn monitor(&mut self, gdrr_thread_handlers: Vec<thread::JoinHandle<GDRRStats>>, running: Arc<AtomicBool>) -> Vec<GDRRStats> {
         let start_time = time::Instant::now();
         let max_run_time = self.config.max_run_time.unwrap_or(usize::MAX);
-        let running = Arc::new(AtomicBool::new(true));
-        let r = running.clone();
-
-        ctrlc::set_handler(move || {
-            r.store(false, atomic::Ordering::SeqCst);
-        }).expect("Error setting Ctrl-C handler");
 
         while running.load(atomic::Ordering::SeqCst) &&
             (time::Instant::now() - start_time).as_secs() < max_run_time as u64 {
@@ -101,9 +99,9 @@ impl GlobalSolCollector {
             }
         }
         //Wait for them to finish
-        for handler in gdrr_thread_handlers {
-            handler.join().expect("Error joining GDRR thread");
-        }
+        let gdrr_stats = gdrr_thread_handlers.into_iter()
+            .map(|handler| handler.join().expect("Error joining GDRR thread"))
+            .collect();
 
         match (self.best_complete_solution.as_ref(), self.best_incomplete_cost.as_ref()) {
             (Some(_best_complete_solution), _) => {
@@ -116,6 +114,8 @@ impl GlobalSolCollector {
                 timed_println!("{}","No Global Solution".bright_red().bold());
             }
         }
+
+        gdrr_stats
     }
 
     fn report_new_complete_solution(&mut self, thread_name: String, solution: SendableSolution) {
@@ -143,7 +143,7 @@ impl GlobalSolCollector {
     fn report_new_incomplete_solution(&mut self, thread_name: String, solution: SendableSolution) {
         if self.best_complete_solution.is_none() {
             if self.best_incomplete_solution.is_none()
-                || (self.cost_comparator)(&solution.cost(), &self.best_incomplete_solution.as_ref().unwrap().cost()) == Ordering::Less {
+                || self.objective.compare(solution.cost(), self.best_incomplete_solution.as_ref().unwrap().cost()) == Ordering::Less {
                 timed_println!("[{}]\t{}{}", thread_name, "<incomplete>\t".bright_green(), util::solution_stats_string(&solution));
                 self.best_incomplete_solution = Some(solution.clone());
             }
@@ -153,7 +153,7 @@ impl GlobalSolCollector {
     fn report_new_incomplete_cost(&mut self, thread_name: String, stats: SolutionStats) {
         if stats.cost.material_cost < self.material_limit.unwrap_or(u64::MAX) {
             if self.best_incomplete_cost.is_none()
-                || (self.cost_comparator)(&stats.cost, &self.best_incomplete_cost.as_ref().unwrap()) == Ordering::Less {
+                || self.objective.compare(&stats.cost, self.best_incomplete_cost.as_ref().unwrap()) == Ordering::Less {
                 timed_println!("[{}]\t{}{}", thread_name, "<incomplete>\t".bright_green(), util::compact_stats_string(&stats));
                 self.best_incomplete_cost = Some(stats.cost.clone());
             }
@@ -4,8 +4,8 @@ use std::sync::mpsc::{Receiver, Sender};
 
 use colored::*;
 
-use crate::core::cost::Cost;
 use crate::optimization::instance::Instance;
+use crate::optimization::objective::Objective;
 use crate::optimization::solutions::problem_solution::ProblemSolution;
 use crate::optimization::solutions::sendable_solution::SendableSolution;
 use crate::optimization::solutions::solution::Solution;
@@ -21,7 +21,7 @@ pub struct LocalSolCollector<'a> {
     instance: Arc<Instance>,
     best_complete_solution: Option<ProblemSolution<'a>>,
     best_incomplete_solution: Option<ProblemSolution<'a>>,
-    cost_comparator: fn(&Cost, &Cost) -> Ordering,
+    objective: Arc<dyn Objective>,
     material_limit: Option<u64>,
     rx_sync: Receiver<SyncMessage>,
     tx_solution_report: Sender<SolutionReportMessage>,
@@ -35,14 +35,14 @@ impl<'a> LocalSolCollector<'a> {
     pub fn new(instance: Arc<Instance>,
                rx_sync: Receiver<SyncMessage>,
                tx_solution_report: Sender<SolutionReportMessage>,
-               cost_comparator: fn(&Cost, &Cost) -> Ordering,
+               objective: Arc<dyn Objective>,
     ) -> Self {
 
         Self {
             instance,
             best_complete_solution : None,
             best_incomplete_solution : None,
-            cost_comparator,
+            objective,
             material_limit : None,
             rx_sync,
             tx_solution_report,
@@ -63,7 +63,7 @@ impl<'a> LocalSolCollector<'a> {
             }
             Some(best_incomplete_solution) => {
                 debug_assert!(solution.cost().material_cost < self.material_limit.unwrap_or(u64::MAX));
-                if (self.cost_comparator)(&solution.cost(), &best_incomplete_solution.cost()) == Ordering::Less {
+                if self.objective.compare(solution.cost(), best_incomplete_solution.cost()) == Ordering::Less {
                     self.accept_solution(solution);
                     self.tx_solution_report();
                 }
@@ -155,8 +155,8 @@ impl<'a> LocalSolCollector<'a> {
     pub fn best_incomplete_solution(&self) -> &Option<ProblemSolution<'a>> {
         &self.best_incomplete_solution
     }
-    pub fn cost_comparator(&self) -> fn(&Cost, &Cost) -> Ordering {
-        self.cost_comparator
+    pub fn objective(&self) -> &Arc<dyn Objective> {
+        &self.objective
     }
 
     pub fn material_limit(&self) -> u64 {
@@ -1,4 +1,4 @@
-use crate::Cost;
+use crate::core::cost::Cost;
 
 pub struct SolutionStats {
     pub cost: Cost,
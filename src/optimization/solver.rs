@@ -0,0 +1,299 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::io::json_format::JsonInstance;
+use crate::io::parser;
+use crate::optimization::config::Config;
+use crate::optimization::gdrr::{GDRR, GDRRStats};
+use crate::optimization::instance::Instance;
+use crate::optimization::objective::Objective;
+use crate::optimization::sol_collectors::global_sol_collector::GlobalSolCollector;
+use crate::optimization::sol_collectors::local_sol_collector::LocalSolCollector;
+use crate::optimization::solutions::sendable_solution::SendableSolution;
+
+/// Solver runs the multithreaded GDRR algorithm on an Instance.
+/// It sets up a GDRR thread (each with its own LocalSolCollector) per configured thread
+/// and monitors them with a GlobalSolCollector until one of the termination conditions is met.
+pub struct Solver {
+    instance: Arc<Instance>,
+    config: Arc<Config>,
+    initial_solution: Option<Arc<SendableSolution>>,
+    objective: Arc<dyn Objective>,
+    running: Arc<AtomicBool>,
+}
+
+impl Solver {
+    /// If the config does not define a seed, a random one is drawn, so that every run can be reproduced
+    pub fn new(instance: Instance, mut config: Config) -> Self {
+        if config.seed.is_none() {
+            config.seed = Some(rand::random());
+        }
+        Self {
+            instance: Arc::new(instance),
+            objective: config.objective(),
+            config: Arc::new(config),
+            initial_solution: None,
+            running: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn from_json_instance(json_instance: &mut JsonInstance, config: Config) -> Self {
+        let instance = parser::generate_instance(json_instance, &config);
+        Self::new(instance, config)
+    }
+
+    /// Warm-starts all GDRR threads from an existing solution of the same instance
+    pub fn with_initial_solution(mut self, initial_solution: SendableSolution) -> Self {
+        assert!(Arc::ptr_eq(initial_solution.instance(), &self.instance), "initial solution belongs to another instance");
+        self.initial_solution = Some(Arc::new(initial_solution));
+        self
+    }
+
+    /// Replaces the objective selected in the config, e.g. by a custom implementation
+    pub fn with_objective(mut self, objective: Arc<dyn Objective>) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// The solver terminates prematurely when this flag is set to false (e.g. by a Ctrl+C handler)
+    pub fn with_running_flag(mut self, running: Arc<AtomicBool>) -> Self {
+        self.running = running;
+        self
+    }
+
+    pub fn solve(self) -> SolverResult {
+        let start_time = Instant::now();
+
+        let mut gdrr_thread_handlers = Vec::new();
+        let mut tx_syncs = Vec::new();
+        let (tx_solution_report, rx_solution_report) = channel();
+
+        for i in 0..self.config.n_threads {
+            let instance_thread = self.instance.clone();
+            let config_thread = self.config.clone();
+            let initial_solution_thread = self.initial_solution.clone();
+            let objective_thread = self.objective.clone();
+            let thread_name = format!("T{}", i);
+            let (tx_sync, rx_sync) = channel();
+            let tx_solution_report_thread = tx_solution_report.clone();
+            tx_syncs.push(tx_sync);
+
+            let handle = thread::Builder::new().name(thread_name).spawn(move || {
+                let local_sol_collector = LocalSolCollector::new(instance_thread.clone(), rx_sync, tx_solution_report_thread, objective_thread);
+                let mut gdrr = GDRR::new(&instance_thread, &config_thread, local_sol_collector, i);
+                if let Some(initial_solution) = initial_solution_thread.as_ref() {
+                    gdrr.warm_start(initial_solution);
+                }
+                gdrr.lahc()
+            });
+            gdrr_thread_handlers.push(handle.expect("could not spawn thread"));
+        }
+
+        let mut global_sol_collector = GlobalSolCollector::new(self.instance.clone(), self.config.clone(), tx_syncs, rx_solution_report, self.objective.clone());
+
+        let gdrr_stats = global_sol_collector.monitor(gdrr_thread_handlers, self.running.clone());
+
+        let mut best_complete_solution = global_sol_collector.best_complete_solution().clone();
+        let mut best_incomplete_solution = global_sol_collector.best_incomplete_solution().clone();
+
+        if let Some(max_iterations) = self.config.pattern_reduction_iterations
+            && let Some(best_solution) = best_complete_solution.as_mut().or(best_incomplete_solution.as_mut()) {
+            *best_solution = self.reduce_patterns(best_solution, max_iterations);
+        }
+
+        SolverResult {
+            best_complete_solution,
+            best_incomplete_solution,
+            gdrr_stats,
+            run_time: start_time.elapsed(),
+            seed: self.config.seed.expect("seed should be set"),
+        }
+    }
+
+    /// Runs the post-optimization phase which reduces the number of distinct cutting patterns of a solution, see GDRR::reduce_patterns
+    fn reduce_patterns(&self, solution: &SendableSolution, max_iterations: usize) -> SendableSolution {
+        //The phase runs on the current thread, no other collectors need to be synchronized
+        let (_tx_sync, rx_sync) = channel();
+        let (tx_solution_report, _rx_solution_report) = channel();
+        let local_sol_collector = LocalSolCollector::new(self.instance.clone(), rx_sync, tx_solution_report, self.objective.clone());
+
+        let mut gdrr = GDRR::new(&self.instance, &self.config, local_sol_collector, self.config.n_threads);
+        gdrr.warm_start(solution);
+        let deadline = self.config.pattern_reduction_time.map(|reduction_time| Instant::now() + Duration::from_secs(reduction_time as u64));
+        let reduced_solution = gdrr.reduce_patterns(max_iterations, deadline, &self.running);
+        SendableSolution::new(self.instance.clone(), &reduced_solution)
+    }
+
+    pub fn instance(&self) -> &Arc<Instance> {
+        &self.instance
+    }
+
+    pub fn config(&self) -> &Arc<Config> {
+        &self.config
+    }
+}
+
+/// The outcome of a Solver run
+#[derive(Debug, Clone)]
+pub struct SolverResult {
+    best_complete_solution: Option<SendableSolution>,
+    best_incomplete_solution: Option<SendableSolution>,
+    gdrr_stats: Vec<GDRRStats>,
+    run_time: Duration,
+    seed: u64,
+}
+
+impl SolverResult {
+    /// The best complete solution if one was found, otherwise the best incomplete solution
+    pub fn best_solution(&self) -> Option<&SendableSolution> {
+        self.best_complete_solution.as_ref().or(self.best_incomplete_solution.as_ref())
+    }
+
+    pub fn best_complete_solution(&self) -> &Option<SendableSolution> {
+        &self.best_complete_solution
+    }
+
+    pub fn best_incomplete_solution(&self) -> &Option<SendableSolution> {
+        &self.best_incomplete_solution
+    }
+
+    /// Statistics of every GDRR thread
+    pub fn gdrr_stats(&self) -> &Vec<GDRRStats> {
+        &self.gdrr_stats
+    }
+
+    pub fn n_iterations(&self) -> usize {
+        self.gdrr_stats.iter().map(|s| s.n_iterations).sum()
+    }
+
+    pub fn run_time(&self) -> Duration {
+        self.run_time
+    }
+
+    /// The seed from which the random streams of all threads were derived
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::io::json_format::JsonSolution;
+    use crate::optimization::solutions::solution::Solution;
+
+    #[test]
+    fn knapsack() {
+        //The stock only suffices for either the large item or both of the small, more valuable ones
+        let mut json_instance: JsonInstance = serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": [{"Length": 10, "Height": 10, "Stock": 1, "Cost": 100}],
+            "Items": [{"Length": 10, "Height": 10, "Demand": 1, "Value": 10}, {"Length": 5, "Height": 5, "Demand": 2, "Value": 40}]
+        })).unwrap();
+        let config: Config = serde_json::from_value(json!({
+            "avgNodesRemoved": 6, "blinkRate": 0.01, "leftoverValuationPower": 2, "historyLength": 500,
+            "rotationAllowed": true, "nThreads": 1, "sheetValuationMode": "area", "seed": 0, "maxRRIterations": 10, "objective": "knapsack"
+        })).unwrap();
+        let result = Solver::from_json_instance(&mut json_instance, config).solve();
+
+        let solution = result.best_solution().unwrap();
+        assert_eq!(solution.parttype_qtys(), &vec![1, 0]);
+        assert_eq!(solution.cost().part_value_excluded, 10);
+    }
+
+    #[test]
+    fn trimming_stage() {
+        //With a single stage of V cuts, the items can only be separated from the waste below them by a trimming cut
+        let solve = |stage_mode: &str| {
+            let mut json_instance: JsonInstance = serde_json::from_value(json!({
+                "Name": "test",
+                "Objects": [{"Length": 10, "Height": 10, "Stock": null, "Cost": 100, "FirstCut": "V"}],
+                "Items": [{"Length": 5, "Height": 8, "Demand": 2, "Value": 40}]
+            })).unwrap();
+            let config: Config = serde_json::from_value(json!({
+                "avgNodesRemoved": 6, "blinkRate": 0.01, "leftoverValuationPower": 2, "historyLength": 500,
+                "rotationAllowed": false, "nThreads": 1, "sheetValuationMode": "area", "seed": 0, "maxRRIterations": 100,
+                "maxStages": 1, "stageMode": stage_mode
+            })).unwrap();
+            Solver::from_json_instance(&mut json_instance, config).solve()
+        };
+
+        let result = solve("WithTrimming");
+        let solution = result.best_solution().unwrap();
+        assert_eq!(solution.parttype_qtys(), &vec![0]);
+        assert_eq!(solution.n_layouts(), 1);
+        //The trimmed waste has no value
+        assert_eq!(solution.cost().leftover_value, 0.0);
+
+        //Without it, none of the items fit
+        assert!(solve("Exact").best_solution().is_none());
+    }
+
+    #[test]
+    fn entirely_defective_object() {
+        let mut json_instance: JsonInstance = serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": [
+                {"Length": 10, "Height": 10, "Stock": null, "Cost": 100, "Defects": [{"X": 0, "Y": 0, "Length": 10, "Height": 10}]},
+                {"Length": 10, "Height": 10, "Stock": null, "Cost": 100}
+            ],
+            "Items": [{"Length": 5, "Height": 10, "Demand": 2, "Value": 40}]
+        })).unwrap();
+        let config: Config = serde_json::from_value(json!({
+            "avgNodesRemoved": 6, "blinkRate": 0.01, "leftoverValuationPower": 2, "historyLength": 500,
+            "rotationAllowed": true, "nThreads": 1, "sheetValuationMode": "area", "seed": 0, "maxRRIterations": 100
+        })).unwrap();
+        let solver = Solver::from_json_instance(&mut json_instance, config);
+        assert_eq!(solver.instance().sheets()[0].1, 0);
+
+        let result = solver.solve();
+        let solution = result.best_solution().unwrap();
+        assert_eq!(solution.parttype_qtys(), &vec![0]);
+        assert!(solution.layouts().iter().all(|l| l.sheettype_id() == 1));
+    }
+
+    #[test]
+    fn pattern_reduction() {
+        let mut json_instance: JsonInstance = serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": [{"Length": 10, "Height": 10, "Stock": null, "Cost": 100}],
+            "Items": [{"Length": 5, "Height": 10, "Demand": 4, "Value": 50}]
+        })).unwrap();
+        let config: Config = serde_json::from_value(json!({
+            "avgNodesRemoved": 6, "blinkRate": 0.01, "leftoverValuationPower": 2, "historyLength": 500,
+            "rotationAllowed": true, "nThreads": 1, "sheetValuationMode": "area", "seed": 0, "patternReductionTime": 10
+        })).unwrap();
+        let solver = Solver::from_json_instance(&mut json_instance, config);
+
+        //Two patterns which both produce two items, one of them with rotated items
+        let placement = |x: u64, y: u64, length: u64, height: u64| json!({"Item": 0, "X": x, "Y": y, "Length": length, "Height": height, "Rotated": length == 10});
+        let item = |x: u64, y: u64, length: u64, height: u64| json!({"Length": length, "Height": height, "X": x, "Y": y, "Type": "Item", "Item": 0, "Children": []});
+        let pattern = |orientation: &str, items: [(u64, u64, u64, u64); 2]| json!({
+            "Object": 0, "Usage": 1.0,
+            "Root": {"Length": 10, "Height": 10, "X": 0, "Y": 0, "Orientation": orientation, "Type": "Structure",
+                     "Children": items.map(|(x, y, l, h)| item(x, y, l, h))},
+            "Placements": items.map(|(x, y, l, h)| placement(x, y, l, h))
+        });
+        let json_solution: JsonSolution = serde_json::from_value(json!({
+            "Name": "test",
+            "Objects": json_instance.sheettypes,
+            "Items": json_instance.parttypes,
+            "CuttingPatterns": [pattern("V", [(0, 0, 5, 10), (5, 0, 5, 10)]), pattern("H", [(0, 0, 10, 5), (0, 5, 10, 5)])],
+            "Statistics": {"UsagePct": 100.0, "PartAreaIncludedPct": 100.0, "NObjectsUsed": 2, "MaterialCost": 200, "RunTimeMs": 0, "ConfigPath": ""}
+        })).unwrap();
+        let solution = parser::generate_solution_from_json(&json_solution, solver.instance().clone(), solver.config()).ok().unwrap();
+
+        assert_eq!(solution.cost().n_patterns, 2);
+
+        let reduced_solution = solver.reduce_patterns(&solution, 10);
+        assert_eq!(reduced_solution.cost().n_patterns, 1);
+        assert_eq!(reduced_solution.n_layouts(), 2);
+        assert_eq!(reduced_solution.cost().part_area_excluded, 0);
+        assert_eq!(reduced_solution.cost().material_cost, solution.cost().material_cost);
+    }
+}
@@ -256,6 +256,12 @@ pub fn cached_sorted_empty_nodes_correct(nodes: &Arena<Node>, cached_sorted_empt
     return true;
 }
 
+pub fn no_unmachinable_waste(nodes: &Arena<Node>, sheettype: &SheetType) -> bool {
+    nodes.iter()
+        .filter(|(_i, n)| n.is_empty())
+        .all(|(_i, n)| !sheettype.is_unmachinable_waste(n.width(), n.height()))
+}
+
 pub fn instance_parttypes_and_sheettypes_ids_correct(parttypes: &Vec<(PartType, usize)>, sheettypes: &Vec<(SheetType, usize)>) -> bool {
     parttypes.iter().enumerate().all(|(i, (p, _qty))| {
         p.id() == i
@@ -17,4 +17,42 @@ pub fn compact_stats_string(stats: &SolutionStats) -> String {
         stats.cost.part_area_fraction_included() * 100.0,
         stats.n_sheets,
         stats.cost.material_cost)
-}
\ No newline at end of file
+}
+
+/// SplitMix64 finalizer, maps every input to a well distributed output, e.g. to derive independent seeds
+pub fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// The seed of the random number generator of a thread, derived from the seed of the run
+pub fn thread_seed(seed: u64, thread_index: usize) -> u64 {
+    splitmix64(splitmix64(seed).wrapping_add(thread_index as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitmix64_reference() {
+        //The first outputs of the reference SplitMix64 generator with state 0, which adds the increment before every output
+        let gamma = 0x9E37_79B9_7F4A_7C15u64;
+        assert_eq!(splitmix64(0), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(splitmix64(gamma), 0x6E78_9E6A_A1B9_65F4);
+        assert_eq!(splitmix64(gamma.wrapping_mul(2)), 0x06C4_5D18_8009_454F);
+    }
+
+    #[test]
+    fn thread_seeds() {
+        assert_eq!(thread_seed(7, 3), thread_seed(7, 3));
+        //Neighbouring seeds and threads do not share any streams
+        let seeds = (0..4).flat_map(|seed| (0..8).map(move |thread_index| thread_seed(seed, thread_index))).collect::<Vec<_>>();
+        let mut unique_seeds = seeds.clone();
+        unique_seeds.sort();
+        unique_seeds.dedup();
+        assert_eq!(unique_seeds.len(), seeds.len());
+    }
+}